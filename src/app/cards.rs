@@ -1,84 +1,323 @@
 use super::gestures::PointerEvent;
-use super::{log, Presenter, Reaction, State, ViewController};
-use crate::logic::{Challenge, ChoiceSelection, Game, Quantity};
+use super::{log, Listener, Presenter, Reaction, State, ViewController};
+use crate::logic::{
+    Answer, Category, Challenge, ChallengeKind, Choice, ChoiceSelection, Clock, Game, GameMode, Grade, Quantity,
+};
 
-use web_sys::{window, Element, MouseEvent, TouchEvent};
+use std::time::Duration;
+
+use wasm_bindgen::JsCast;
+use web_sys::{window, Element, HtmlInputElement, MouseEvent, TouchEvent};
+
+const SWIPE_THRESHOLD: i32 = 100;
+const COUNTDOWN_INTERVAL_MS: i32 = 200;
+
+/// Clock backed by `performance.now()` of the page.
+#[derive(Debug)]
+struct PerformanceClock;
+
+impl Clock for PerformanceClock {
+    fn now(&self) -> Duration {
+        let millis = window().unwrap().performance().expect("performance missing").now();
+        Duration::from_secs_f64(millis / 1000.0)
+    }
+}
 
 struct Card {
+    kind: ChallengeKind,
     card: Element,
-    left: Element,
-    right: Element,
+    equal: Element,
+    /// Left and right side for two-sided cards, a list of tappable options for multiple-choice ones or a list of
+    /// draggable items for ordering ones.
+    options: Vec<Element>,
+    /// Current arrangement of the items (indices into `options`), ordering cards only.
+    order: Vec<usize>,
+    /// Item being dragged at the moment, ordering cards only.
+    dragged: Option<usize>,
+    done: Option<Element>,
+    /// Field for the typed value, estimation cards only.
+    input: Option<HtmlInputElement>,
+    /// Listeners of the card's own elements, they go away together with the card.
+    _listeners: Vec<Listener>,
 }
 
 impl Card {
-    fn new(challenge: Challenge) -> Self {
+    fn new(challenge: &Challenge, presenter: &mut Presenter) -> Self {
         let document = window().unwrap().document().unwrap();
 
         // create card view
         let card = document.create_element("div").expect("create_element failed");
         card.set_class_name("card");
 
-        // create left side of the card
-        let left_choice = challenge.left_choice;
-        let left = document.create_element("div").expect("create_element failed");
-        left.set_class_name("left");
-        left.set_inner_html(&format!("{} {}", left_choice.value, left_choice.unit));
-        card.append_with_node_1(&left).expect("append_with_node_1 failed");
+        // create the question, e.g. "Which is colder?"
+        let prompt = document.create_element("div").expect("create_element failed");
+        prompt.set_class_name("prompt");
+        prompt.set_inner_html(&challenge.prompt());
+        card.append_with_node_1(&prompt).expect("append_with_node_1 failed");
+
+        // hint where the values come from, e.g. "weather"
+        let context = document.create_element("div").expect("create_element failed");
+        context.set_class_name("context");
+        context.set_inner_html(&challenge.context.to_string());
+        card.append_with_node_1(&context).expect("append_with_node_1 failed");
+
+        // create the "equal" marker, it grows while the card is swiped up
+        let equal = document.create_element("div").expect("create_element failed");
+        equal.set_class_name("equal");
+        equal.set_inner_html("=");
+
+        let mut options = Vec::new();
+        let mut done = None;
+        let mut input = None;
+        let mut listeners = Vec::new();
+        if let ChallengeKind::Estimate { .. } = challenge.kind {
+            // the value to convert is shown above a field for the answer
+            card.set_class_name("card estimate");
+            let choice = &challenge.choices[0];
+            let shown = document.create_element("div").expect("create_element failed");
+            shown.set_class_name("option");
+            shown.set_inner_html(&choice.to_string());
+            card.append_with_node_1(&shown).expect("append_with_node_1 failed");
+            options.push(shown);
+
+            let field = document
+                .create_element("input")
+                .expect("create_element failed")
+                .dyn_into::<HtmlInputElement>()
+                .expect("dyn_into failed");
+            field.set_type("number");
+            field.set_attribute("step", "any").expect("set step failed");
+            card.append_with_node_1(&field).expect("append_with_node_1 failed");
+            input = Some(field);
+
+            // create the button confirming the value
+            let button = document.create_element("div").expect("create_element failed");
+            button.set_class_name("done");
+            button.set_inner_html("&#10003;");
+            card.append_with_node_1(&button).expect("append_with_node_1 failed");
+            listeners.push(
+                presenter.listen(&button, "click", |ctrl: &mut CardsController, _: MouseEvent| {
+                    ctrl.submit_estimate()
+                }),
+            );
+            done = Some(button);
+        } else if challenge.kind == ChallengeKind::Order {
+            // items are stacked and reordered by dragging them up and down
+            card.set_class_name("card ordering");
+            for (idx, choice) in challenge.choices.iter().enumerate() {
+                let item = document.create_element("div").expect("create_element failed");
+                item.set_class_name("option");
+                item.set_inner_html(&choice.to_string());
+                card.append_with_node_1(&item).expect("append_with_node_1 failed");
+                listeners.push(presenter.listen(
+                    &item,
+                    "mousedown",
+                    move |ctrl: &mut CardsController, _: MouseEvent| ctrl.start_drag(idx),
+                ));
+                listeners.push(presenter.listen(
+                    &item,
+                    "touchstart",
+                    move |ctrl: &mut CardsController, _: TouchEvent| ctrl.start_drag(idx),
+                ));
+                options.push(item);
+            }
+
+            // create the button confirming the order
+            let button = document.create_element("div").expect("create_element failed");
+            button.set_class_name("done");
+            button.set_inner_html("&#10003;");
+            card.append_with_node_1(&button).expect("append_with_node_1 failed");
+            listeners.push(
+                presenter.listen(&button, "click", |ctrl: &mut CardsController, _: MouseEvent| {
+                    ctrl.submit_order()
+                }),
+            );
+            done = Some(button);
+        } else if let [left_choice, right_choice] = challenge.choices.as_slice() {
+            // create left side of the card
+            let left = document.create_element("div").expect("create_element failed");
+            left.set_class_name(&side_class_name("left", left_choice));
+            left.set_inner_html(&left_choice.to_string());
+            card.append_with_node_1(&left).expect("append_with_node_1 failed");
+
+            // put the "equal" marker between the sides
+            card.append_with_node_1(&equal).expect("append_with_node_1 failed");
+
+            // create right side of the card
+            let right = document.create_element("div").expect("create_element failed");
+            right.set_class_name(&side_class_name("right", right_choice));
+            right.set_inner_html(&right_choice.to_string());
+            card.append_with_node_1(&right).expect("append_with_node_1 failed");
+
+            options.push(left);
+            options.push(right);
+        } else {
+            // multiple choices are stacked and picked by tapping
+            card.set_class_name("card multiple");
+            for (idx, choice) in challenge.choices.iter().enumerate() {
+                let option = document.create_element("div").expect("create_element failed");
+                option.set_class_name("option");
+                option.set_inner_html(&choice.to_string());
+                card.append_with_node_1(&option).expect("append_with_node_1 failed");
+                listeners.push(
+                    presenter.listen(&option, "click", move |ctrl: &mut CardsController, _: MouseEvent| {
+                        ctrl.select(ChoiceSelection::Nth(idx))
+                    }),
+                );
+                options.push(option);
+            }
+            card.append_with_node_1(&equal).expect("append_with_node_1 failed");
+        }
+
+        let order = if challenge.kind == ChallengeKind::Order {
+            (0..options.len()).collect()
+        } else {
+            Vec::new()
+        };
+        Self {
+            kind: challenge.kind,
+            card,
+            equal,
+            options,
+            order,
+            dragged: None,
+            done,
+            input,
+            _listeners: listeners,
+        }
+    }
+
+    fn is_pick(&self) -> bool {
+        self.kind == ChallengeKind::Pick
+    }
 
-        // create right side of the card
-        let right_choice = challenge.right_choice;
-        let right = document.create_element("div").expect("create_element failed");
-        right.set_class_name("right");
-        right.set_inner_html(&format!("{} {}", right_choice.value, right_choice.unit));
-        card.append_with_node_1(&right).expect("append_with_node_1 failed");
+    fn is_two_sided(&self) -> bool {
+        self.is_pick() && self.options.len() == 2
+    }
 
-        Self { card, left, right }
+    fn is_ordering(&self) -> bool {
+        self.kind == ChallengeKind::Order
+    }
+
+    /// Moves the dragged item by `translate_y` rounded to whole item positions.
+    fn drop_dragged(&mut self, translate_y: i32) {
+        if let Some(dragged) = self.dragged.take() {
+            let item_step = match self.order.as_slice() {
+                [first, second, ..] => {
+                    self.options[*second].get_bounding_client_rect().top()
+                        - self.options[*first].get_bounding_client_rect().top()
+                }
+                _ => 0.0,
+            };
+            let position = self.order.iter().position(|idx| *idx == dragged).unwrap();
+            if item_step > 0.0 {
+                let shift = (translate_y as f64 / item_step).round() as i32;
+                let new_position = (position as i32 + shift).clamp(0, self.order.len() as i32 - 1) as usize;
+                self.order.remove(position);
+                self.order.insert(new_position, dragged);
+            }
+            self.options[dragged]
+                .remove_attribute("style")
+                .expect("remove style failed");
+
+            // re-appending moves the elements so this rebuilds the list in the new order
+            for idx in &self.order {
+                self.card
+                    .append_with_node_1(&self.options[*idx])
+                    .expect("append_with_node_1 failed");
+            }
+            if let Some(ref done) = self.done {
+                self.card.append_with_node_1(done).expect("append_with_node_1 failed");
+            }
+        }
     }
 
-    fn set_translate(&mut self, translate_x: i32) {
+    fn set_translate(&mut self, translate_x: i32, translate_y: i32) {
+        if self.is_ordering() {
+            // ordering cards stay put, only the dragged item follows the pointer
+            if let Some(dragged) = self.dragged {
+                self.options[dragged]
+                    .set_attribute("style", &format!("transform: translate(0px, {}px);", translate_y))
+                    .expect("set style failed");
+            }
+            return;
+        }
+        if !self.is_pick() {
+            // estimation cards are answered by typing so they don't move at all
+            return;
+        }
+
         self.card
             .set_attribute(
                 "style",
                 &format!(
-                    "transform: translate({}px, 0px) rotate({}deg);",
+                    "transform: translate({}px, {}px) rotate({}deg);",
                     translate_x,
+                    translate_y.min(0),
                     translate_x as f32 / 10.0
                 ),
             )
             .expect("set style failed");
 
-        let scale_adjust = translate_x as f32 / 100.0;
-        let left_scale = if scale_adjust > 1.0 { 0.0 } else { 1.0 - scale_adjust };
-        self.left
-            .set_attribute("style", &format!("transform: scale({}, {});", left_scale, left_scale))
-            .expect("set style failed");
-        let right_scale = if scale_adjust < -1.0 { 0.0 } else { 1.0 + scale_adjust };
-        self.right
-            .set_attribute("style", &format!("transform: scale({}, {});", right_scale, right_scale))
+        let equal_scale = (-translate_y as f32 / SWIPE_THRESHOLD as f32).clamp(0.0, 1.0);
+        self.equal
+            .set_attribute("style", &format!("transform: scale({}, {});", equal_scale, equal_scale))
             .expect("set style failed");
+
+        if let [left, right] = self.options.as_slice() {
+            let scale_adjust = translate_x as f32 / 100.0;
+            let left_scale = if scale_adjust > 1.0 { 0.0 } else { 1.0 - scale_adjust };
+            left.set_attribute("style", &format!("transform: scale({}, {});", left_scale, left_scale))
+                .expect("set style failed");
+            let right_scale = if scale_adjust < -1.0 { 0.0 } else { 1.0 + scale_adjust };
+            right
+                .set_attribute("style", &format!("transform: scale({}, {});", right_scale, right_scale))
+                .expect("set style failed");
+        }
     }
 }
 
 pub struct CardsController {
     game: Game,
     view: Option<Element>,
+    presenter: Option<Presenter>,
+    countdown: Option<Element>,
+    countdown_interval: Option<i32>,
+    /// Remaining lives or cards and the solution of the last mistake for games that go on after mistakes.
+    status: Option<Element>,
+    /// Grade of the last answer, orders show it since they can be partly right.
+    last_grade: Option<Grade>,
 
     pan_start_x: Option<i32>,
+    pan_start_y: Option<i32>,
     translate_x: i32,
+    translate_y: i32,
     card: Option<Card>,
 }
 
 impl CardsController {
-    pub fn new(quantity: Quantity) -> Self {
+    pub fn new(quantity: Quantity, categories: &[Category], mode: GameMode) -> Self {
         Self {
-            game: Game::new_with_single_quantity(quantity),
+            game: Game::new_with_single_quantity(quantity, categories, mode, Box::new(PerformanceClock)),
             view: None,
+            presenter: None,
+            countdown: None,
+            countdown_interval: None,
+            status: None,
+            last_grade: None,
             pan_start_x: None,
+            pan_start_y: None,
             translate_x: 0,
+            translate_y: 0,
             card: None,
         }
     }
 
+    fn show_next_card(&mut self) {
+        let mut presenter = self.presenter.clone().expect("presenter missing");
+        self.replace_card(Card::new(&self.game.challenge, &mut presenter));
+    }
+
     fn replace_card(&mut self, card: Card) {
         if let Some(ref old_card) = self.card {
             old_card.card.remove();
@@ -91,51 +330,152 @@ impl CardsController {
         self.card = Some(card);
     }
 
-    fn update_card_translation_with_event<T: PointerEvent>(&mut self, event: T) -> i32 {
-        if let (Some(pan_start_x), Some(current_x)) = (self.pan_start_x, event.get_x()) {
-            let translation = current_x - pan_start_x;
-            self.update_card_translation(translation);
+    fn update_card_translation_with_event<T: PointerEvent>(&mut self, event: T) -> (i32, i32) {
+        if let (Some(pan_start_x), Some(current_x), Some(pan_start_y), Some(current_y)) =
+            (self.pan_start_x, event.get_x(), self.pan_start_y, event.get_y())
+        {
+            let translation = (current_x - pan_start_x, current_y - pan_start_y);
+            self.update_card_translation(translation.0, translation.1);
             translation
         } else {
-            self.translate_x
+            (self.translate_x, self.translate_y)
         }
     }
 
-    fn update_card_translation(&mut self, translation_x: i32) {
+    fn update_card_translation(&mut self, translation_x: i32, translation_y: i32) {
         self.translate_x = translation_x;
+        self.translate_y = translation_y;
         if let Some(ref mut card) = self.card {
-            card.set_translate(translation_x);
+            card.set_translate(translation_x, translation_y);
         }
     }
 
     fn pointer_start<T: PointerEvent>(&mut self, event: T) -> Option<Reaction> {
         self.pan_start_x = event.get_x();
+        self.pan_start_y = event.get_y();
         self.translate_x = 0;
+        self.translate_y = 0;
         None
     }
 
+    fn start_drag(&mut self, idx: usize) -> Option<Reaction> {
+        if let Some(ref mut card) = self.card {
+            card.dragged = Some(idx);
+        }
+        None
+    }
+
+    fn submit_order(&mut self) -> Option<Reaction> {
+        let order = self.card.as_ref().map(|card| card.order.clone()).unwrap_or_default();
+        let grade = self.game.answer(Answer::Order(order));
+        self.answered(grade)
+    }
+
+    fn submit_estimate(&mut self) -> Option<Reaction> {
+        let value = self
+            .card
+            .as_ref()
+            .and_then(|card| card.input.as_ref())
+            .and_then(|input| input.value().trim().parse().ok());
+        if let Some(value) = value {
+            let grade = self.game.answer(Answer::Estimate(value));
+            self.answered(grade)
+        } else {
+            log!("not a number, ignoring");
+            None
+        }
+    }
+
+    fn select(&mut self, selection: ChoiceSelection) -> Option<Reaction> {
+        let grade = self.game.pick(selection);
+        self.answered(grade)
+    }
+
+    fn answered(&mut self, grade: Grade) -> Option<Reaction> {
+        log!("answered with grade: {}/{}", grade.points, grade.max_points);
+        self.last_grade = Some(grade);
+        if !self.game.in_progress {
+            self.game_over()
+        } else {
+            // game is still on -> set new card
+            self.show_next_card();
+            self.update_status();
+            None
+        }
+    }
+
+    fn update_status(&mut self) {
+        if let Some(ref status) = self.status {
+            let lives = match (self.game.lives_left(), self.game.cards_left()) {
+                (Some(lives), _) => "&hearts;".repeat(lives),
+                (_, Some(cards)) => format!("{} left", cards),
+                _ => String::new(),
+            };
+            let score = match self.last_grade {
+                Some(grade) if grade.max_points > 1 => format!("{}/{} in order", grade.points, grade.max_points),
+                _ => String::new(),
+            };
+            let correction = match (self.game.correction(), score.is_empty()) {
+                (Some(correction), true) => format!("&#10007; {}", correction),
+                (Some(correction), false) => format!("&#10007; {}, {}", score, correction),
+                (None, false) => format!("&#10003; {}", score),
+                (None, true) => String::new(),
+            };
+            status.set_inner_html(&format!("{}<br>{}", lives, correction));
+        }
+    }
+
+    fn tick(&mut self) -> Option<Reaction> {
+        self.game.tick();
+        if let (Some(ref countdown), Some(time_left)) = (&self.countdown, self.game.time_left()) {
+            countdown.set_inner_html(&format!("{}", time_left.as_secs_f32().ceil()));
+        }
+        if !self.game.in_progress {
+            self.game_over()
+        } else {
+            None
+        }
+    }
+
+    fn game_over(&mut self) -> Option<Reaction> {
+        // show the summary once the event processing is done
+        log!("ending game");
+        if let (Some(ref mut presenter), Some(handle)) = (&mut self.presenter, self.countdown_interval.take()) {
+            presenter.clear_interval(handle);
+        }
+        Some(Reaction::Transition(State::Ended(self.game.summary())))
+    }
+
     fn pointer_end<T: PointerEvent>(&mut self, event: T) -> Option<Reaction> {
         let mut reaction = None;
-        let translate_x = self.update_card_translation_with_event(event);
-        log!("pan ended with translate_x: {}", translate_x);
-        if translate_x.abs() > 100 {
-            let selection = if translate_x < 0 {
-                ChoiceSelection::Left
-            } else {
-                ChoiceSelection::Right
-            };
-            self.game.pick(selection);
-            if !self.game.in_progress {
-                // transition to Menu once the gesture processing is done
-                log!("ending game");
-                reaction = Some(Reaction::Transition(State::Menu));
-            } else {
-                // game is still on -> set new card
-                self.replace_card(Card::new(self.game.challenge));
+        let (translate_x, translate_y) = self.update_card_translation_with_event(event);
+        log!("pan ended with translate: {}, {}", translate_x, translate_y);
+        if let Some(ref mut card) = self.card {
+            if card.is_ordering() {
+                card.drop_dragged(translate_y);
+                self.pan_start_x = None;
+                self.pan_start_y = None;
+                self.update_card_translation(0, 0);
+                return None;
             }
         }
+        let pick = matches!(self.card, Some(ref card) if card.is_pick());
+        let two_sided = matches!(self.card, Some(ref card) if card.is_two_sided());
+        let selection = if pick && -translate_y > SWIPE_THRESHOLD && -translate_y > translate_x.abs() {
+            Some(ChoiceSelection::Equal)
+        } else if two_sided && translate_x < -SWIPE_THRESHOLD {
+            Some(ChoiceSelection::Nth(0))
+        } else if two_sided && translate_x > SWIPE_THRESHOLD {
+            Some(ChoiceSelection::Nth(1))
+        } else {
+            None
+        };
+        if let Some(selection) = selection {
+            reaction = self.select(selection);
+        }
         self.pan_start_x = None;
-        self.update_card_translation(0);
+        self.pan_start_y = None;
+        self.update_card_translation(0, 0);
         reaction
     }
 
@@ -155,10 +495,36 @@ impl ViewController for CardsController {
         let view = document.create_element("div").expect("create_element failed");
         view.set_class_name("cards");
         self.view = Some(view.clone());
+        self.presenter = Some(presenter.clone());
 
         // create card and add it to the view
-        let card = Card::new(self.game.challenge);
-        self.replace_card(card);
+        self.show_next_card();
+
+        // timed games show the remaining seconds above the card
+        if self.game.time_left().is_some() {
+            let countdown = document.create_element("div").expect("create_element failed");
+            countdown.set_class_name("countdown");
+            view.append_with_node_1(&countdown).expect("append_with_node_1 failed");
+            self.countdown = Some(countdown);
+            self.countdown_interval = Some(presenter.add_interval(COUNTDOWN_INTERVAL_MS, CardsController::tick));
+            self.tick();
+        }
+
+        // endless games need a way out
+        let quit = document.create_element("div").expect("create_element failed");
+        quit.set_class_name("quit");
+        quit.set_inner_html("&#10005;");
+        view.append_with_node_1(&quit).expect("append_with_node_1 failed");
+        presenter.add_event_listener(&quit, "click", |ctrl: &mut CardsController, _: MouseEvent| {
+            ctrl.game_over()
+        });
+
+        // lives and corrections go below the card
+        let status = document.create_element("div").expect("create_element failed");
+        status.set_class_name("status");
+        view.append_with_node_1(&status).expect("append_with_node_1 failed");
+        self.status = Some(status);
+        self.update_status();
 
         // attach gestures
         presenter.add_event_listener(&view, "mousedown", CardsController::pointer_start::<MouseEvent>);
@@ -178,8 +544,23 @@ impl ViewController for CardsController {
         if let Some(ref view) = self.view {
             view.remove();
         }
+        if let (Some(ref mut presenter), Some(handle)) = (&mut self.presenter, self.countdown_interval.take()) {
+            presenter.clear_interval(handle);
+        }
+        self.countdown = None;
+        self.status = None;
         self.pan_start_x = None;
+        self.pan_start_y = None;
         self.view = None;
+        self.presenter = None;
         self.card = None;
     }
 }
+
+/// Expressions are longer than plain values so their side gets a smaller font.
+fn side_class_name(side: &str, choice: &Choice) -> String {
+    match choice.expression {
+        Some(_) => format!("{} expression", side),
+        None => side.to_string(),
+    }
+}
@@ -1,28 +1,87 @@
-use crate::logic::Quantity;
+use crate::logic::{Category, GameMode, Quantity};
 
 use super::{log, Presenter, Reaction, State, ViewController};
 
 use wasm_bindgen::JsCast;
-use web_sys::{window, Document, Element, HtmlImageElement};
+use web_sys::{window, Document, Element, HtmlImageElement, MouseEvent};
 
 const MENU_RADIUS_VH: f32 = 20.0;
-const QUANTITIES: [Quantity; 7] = [
+const QUANTITIES: [Quantity; 13] = [
     Quantity::Temperature,
+    Quantity::TemperatureDifference,
     Quantity::Length,
     Quantity::Area,
     Quantity::Volume,
     Quantity::Mass,
     Quantity::Energy,
     Quantity::Pressure,
+    Quantity::Time,
+    Quantity::WindSpeed,
+    Quantity::Power,
+    Quantity::Concentration,
+    Quantity::ExtremeLength,
 ];
+const CONVERSIONS: &[Category] = &[Category::Conversion];
+const WITHIN_SYSTEM: &[Category] = &[Category::Conversion, Category::WithinSystem];
+const ALL_CATEGORIES: &[Category] = &[Category::Conversion, Category::WithinSystem, Category::Substance];
 
 pub struct MenuController {
     view: Option<Element>,
+    mode_button: Option<Element>,
+    mode: GameMode,
+    category_button: Option<Element>,
+    categories: &'static [Category],
 }
 
 impl Default for MenuController {
     fn default() -> Self {
-        Self { view: None }
+        Self {
+            view: None,
+            mode_button: None,
+            mode: GameMode::SuddenDeath,
+            category_button: None,
+            categories: CONVERSIONS,
+        }
+    }
+}
+
+impl MenuController {
+    fn toggle_mode(&mut self) -> Option<Reaction> {
+        self.mode = match self.mode {
+            GameMode::SuddenDeath => GameMode::blitz(),
+            GameMode::Blitz { .. } => GameMode::lives(),
+            GameMode::Lives { .. } => GameMode::round(),
+            GameMode::Round { .. } => GameMode::practice(),
+            GameMode::Practice { .. } => GameMode::SuddenDeath,
+        };
+        if let Some(ref mode_button) = self.mode_button {
+            mode_button.set_inner_html(mode_to_string(self.mode));
+        }
+        None
+    }
+
+    /// Adds unit pairs within one system (e.g. mL and L), then the ones for a substance (e.g. cups and grams of
+    /// flour) and starts over, conversions are always played.
+    fn toggle_categories(&mut self) -> Option<Reaction> {
+        self.categories = if self.categories == CONVERSIONS {
+            WITHIN_SYSTEM
+        } else if self.categories == WITHIN_SYSTEM {
+            ALL_CATEGORIES
+        } else {
+            CONVERSIONS
+        };
+        if let Some(ref category_button) = self.category_button {
+            category_button.set_inner_html(categories_to_string(self.categories));
+        }
+        None
+    }
+
+    fn play(&mut self, quantity: Quantity) -> Option<Reaction> {
+        Some(Reaction::Transition(State::Playing(
+            quantity,
+            self.categories,
+            self.mode,
+        )))
     }
 }
 
@@ -60,10 +119,32 @@ impl ViewController for MenuController {
         }
         Button::new_all_units_button(&mut presenter, &document, &view, "all").place_in_circle(
             MENU_RADIUS_VH,
-            7,
+            QUANTITIES.len(),
             buttons_in_circle,
         );
 
+        // create game mode switch below the circle
+        let mode_button = document.create_element("div").expect("create_element failed");
+        mode_button.set_class_name("mode-button");
+        mode_button.set_inner_html(mode_to_string(self.mode));
+        view.append_with_node_1(&mode_button)
+            .expect("append_with_node_1 failed");
+        presenter.add_event_listener(&mode_button, "click", |ctrl: &mut MenuController, _: MouseEvent| {
+            ctrl.toggle_mode()
+        });
+        self.mode_button = Some(mode_button);
+
+        // and the category switch below it
+        let category_button = document.create_element("div").expect("create_element failed");
+        category_button.set_class_name("category-button");
+        category_button.set_inner_html(categories_to_string(self.categories));
+        view.append_with_node_1(&category_button)
+            .expect("append_with_node_1 failed");
+        presenter.add_event_listener(&category_button, "click", |ctrl: &mut MenuController, _: MouseEvent| {
+            ctrl.toggle_categories()
+        });
+        self.category_button = Some(category_button);
+
         view
     }
 
@@ -73,18 +154,46 @@ impl ViewController for MenuController {
             view.remove();
         }
         self.view = None;
+        self.mode_button = None;
+        self.category_button = None;
     }
 }
 
 fn quantity_to_string(quantity: Quantity) -> &'static str {
     match quantity {
         Quantity::Temperature => "C/F",
+        Quantity::TemperatureDifference => "&Delta;C/&Delta;F",
         Quantity::Length => "km/M",
         Quantity::Area => "m^2/sq ft",
         Quantity::Volume => "L/fl oz",
         Quantity::Mass => "kg/lbs",
         Quantity::Energy => "cal/J",
         Quantity::Pressure => "psi/kPa",
+        Quantity::Time => "h/min",
+        Quantity::WindSpeed => "Bft/kn",
+        Quantity::Power => "dBm/mW",
+        Quantity::Concentration => "mg/dL",
+        Quantity::ExtremeLength => "ly/nm",
+    }
+}
+
+fn mode_to_string(mode: GameMode) -> &'static str {
+    match mode {
+        GameMode::SuddenDeath => "&infin;",
+        GameMode::Blitz { .. } => "60s",
+        GameMode::Lives { .. } => "&hearts;&hearts;&hearts;",
+        GameMode::Round { .. } => "20",
+        GameMode::Practice { .. } => "&#9998;",
+    }
+}
+
+fn categories_to_string(categories: &[Category]) -> &'static str {
+    if categories.contains(&Category::Substance) {
+        "km/mi + km/m + cup/g"
+    } else if categories.contains(&Category::WithinSystem) {
+        "km/mi + km/m"
+    } else {
+        "km/mi"
     }
 }
 
@@ -108,6 +217,15 @@ impl Button {
         inner_html: &str,
         reaction: Reaction,
     ) -> Self {
+        let button = Self::create(document, parent, icon_src, inner_html);
+
+        // attach handlers
+        presenter.add_event_reaction(&button.button, "click", reaction);
+
+        button
+    }
+
+    fn create(document: &Document, parent: &Element, icon_src: &str, inner_html: &str) -> Self {
         let icon: HtmlImageElement = document
             .create_element("img")
             .expect("create_element failed")
@@ -123,9 +241,6 @@ impl Button {
         button.append_with_node_1(&icon).expect("append_with_node_1 failed");
         parent.append_with_node_1(&button).expect("append_with_node_1 failed");
 
-        // attach handlers
-        presenter.add_event_reaction(&button, "click", reaction);
-
         Self { button, icon }
     }
 
@@ -136,14 +251,13 @@ impl Button {
         inner_html: &str,
         quantity: Quantity,
     ) -> Self {
-        Self::new_button(
-            presenter,
-            document,
-            parent,
-            &quantity_to_icon_src(quantity),
-            inner_html,
-            Reaction::Transition(State::Playing(quantity)),
-        )
+        let button = Self::create(document, parent, &quantity_to_icon_src(quantity), inner_html);
+        presenter.add_event_listener(
+            &button.button,
+            "click",
+            move |ctrl: &mut MenuController, _: MouseEvent| ctrl.play(quantity),
+        );
+        button
     }
 
     fn new_all_units_button(
@@ -152,14 +266,11 @@ impl Button {
         parent: &Element,
         inner_html: &str,
     ) -> Self {
-        Self::new_button(
-            presenter,
-            document,
-            parent,
-            "assets/temperature.svg",
-            inner_html,
-            Reaction::Transition(State::Playing(Quantity::Pressure)),
-        )
+        let button = Self::create(document, parent, "assets/temperature.svg", inner_html);
+        presenter.add_event_listener(&button.button, "click", |ctrl: &mut MenuController, _: MouseEvent| {
+            ctrl.play(Quantity::Pressure)
+        });
+        button
     }
 
     fn place_in_circle(&self, radius_vh: f32, idx: usize, count: usize) {
@@ -1,19 +1,22 @@
-use crate::logic::{GameSummary, Quantity};
+use crate::logic::{Category, GameMode, GameSummary, Quantity};
 
 use super::log;
 
+use std::any::Any;
 use std::cell::RefCell;
 use std::rc::{Rc, Weak};
 
 use cards::CardsController;
 use menu::MenuController;
+use summary::SummaryController;
 use wasm_bindgen::JsCast;
 use wasm_bindgen::{convert::FromWasmAbi, prelude::Closure};
-use web_sys::Element;
+use web_sys::{window, Element};
 
 mod cards;
 mod gestures;
 mod menu;
+mod summary;
 
 #[derive(Clone)]
 pub struct App {
@@ -43,20 +46,94 @@ pub struct Presenter {
     controller: Weak<RefCell<AppController>>,
 }
 
+/// Event listener registered with `Presenter::listen`, it's removed from its element once dropped.
+pub struct Listener {
+    _registration: Box<dyn Any>,
+}
+
+struct Registration<E> {
+    element: Element,
+    event_name: String,
+    closure: Closure<dyn FnMut(E)>,
+}
+
+impl<E> Drop for Registration<E> {
+    fn drop(&mut self) {
+        self.element
+            .remove_event_listener_with_callback(&self.event_name, self.closure.as_ref().unchecked_ref())
+            .unwrap();
+    }
+}
+
 impl Presenter {
-    pub fn add_event_listener<VC, F, E>(&mut self, element: &Element, event_name: &str, mut callback: F)
+    pub fn add_event_listener<VC, F, E>(&mut self, element: &Element, event_name: &str, callback: F)
+    where
+        F: 'static + FnMut(&mut VC, E) -> Option<Reaction>,
+        E: 'static + Clone + FromWasmAbi,
+        AppController: VCMapper<VC>,
+    {
+        let closure = self.event_closure(callback);
+        element
+            .add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+            .unwrap();
+        closure.forget();
+    }
+
+    /// Same as `add_event_listener` but the listener only lives as long as the returned handle, for elements that
+    /// come and go while the view is shown (e.g. cards).
+    pub fn listen<VC, F, E>(&mut self, element: &Element, event_name: &str, callback: F) -> Listener
+    where
+        F: 'static + FnMut(&mut VC, E) -> Option<Reaction>,
+        E: 'static + Clone + FromWasmAbi,
+        AppController: VCMapper<VC>,
+    {
+        let closure = self.event_closure(callback);
+        element
+            .add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+            .unwrap();
+        Listener {
+            _registration: Box::new(Registration {
+                element: element.clone(),
+                event_name: event_name.to_string(),
+                closure,
+            }),
+        }
+    }
+
+    fn event_closure<VC, F, E>(&self, mut callback: F) -> Closure<dyn FnMut(E)>
     where
         F: 'static + FnMut(&mut VC, E) -> Option<Reaction>,
         E: 'static + Clone + FromWasmAbi,
         AppController: VCMapper<VC>,
+    {
+        let controller = self.controller.clone();
+        Closure::wrap(Box::new(move |event: E| {
+            let reaction = if let Some(app_controller) = controller.upgrade() {
+                app_controller
+                    .borrow_mut()
+                    .map_vc(|vc: &mut VC| callback(vc, event.clone()))
+            } else {
+                None
+            };
+            if let Some(Some(reaction)) = reaction {
+                if let Some(app_controller) = controller.upgrade() {
+                    AppController::react(app_controller, reaction);
+                }
+            }
+        }) as Box<dyn FnMut(_)>)
+    }
+
+    /// Calls `callback` every `timeout_ms` milliseconds, returns the handle for `clear_interval`.
+    pub fn add_interval<VC, F>(&mut self, timeout_ms: i32, mut callback: F) -> i32
+    where
+        F: 'static + FnMut(&mut VC) -> Option<Reaction>,
+        AppController: VCMapper<VC>,
     {
         let closure = {
             let controller = self.controller.clone();
-            Closure::wrap(Box::new(move |event: E| {
+            Closure::wrap(Box::new(move || {
                 let reaction = if let Some(app_controller) = controller.upgrade() {
-                    app_controller
-                        .borrow_mut()
-                        .map_vc(|vc: &mut VC| callback(vc, event.clone()))
+                    app_controller.borrow_mut().map_vc(|vc: &mut VC| callback(vc))
                 } else {
                     None
                 };
@@ -65,12 +142,18 @@ impl Presenter {
                         AppController::react(app_controller, reaction);
                     }
                 }
-            }) as Box<dyn FnMut(_)>)
+            }) as Box<dyn FnMut()>)
         };
-        element
-            .add_event_listener_with_callback(event_name, closure.as_ref().unchecked_ref())
+        let handle = window()
+            .unwrap()
+            .set_interval_with_callback_and_timeout_and_arguments_0(closure.as_ref().unchecked_ref(), timeout_ms)
             .unwrap();
         closure.forget();
+        handle
+    }
+
+    pub fn clear_interval(&mut self, handle: i32) {
+        window().unwrap().clear_interval_with_handle(handle);
     }
 
     pub fn add_event_reaction(&mut self, element: &Element, event_name: &str, reaction: Reaction) {
@@ -78,7 +161,7 @@ impl Presenter {
             let controller = self.controller.clone();
             Closure::wrap(Box::new(move || {
                 if let Some(app_controller) = controller.upgrade() {
-                    AppController::react(app_controller, reaction);
+                    AppController::react(app_controller, reaction.clone());
                 }
             }) as Box<dyn FnMut()>)
         };
@@ -95,16 +178,16 @@ impl From<Weak<RefCell<AppController>>> for Presenter {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Debug)]
 pub enum Reaction {
     Transition(State),
 }
 
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub enum State {
     Menu,
     Settings,
-    Playing(Quantity),
+    Playing(Quantity, &'static [Category], GameMode),
     Ended(GameSummary),
 }
 
@@ -117,6 +200,7 @@ pub struct AppController {
     content: Element,
     menu_controller: Option<MenuController>,
     cards_controller: Option<CardsController>,
+    summary_controller: Option<SummaryController>,
 }
 
 pub trait VCMapper<VC> {
@@ -127,15 +211,11 @@ pub trait VCMapper<VC> {
 }
 
 impl VCMapper<MenuController> for AppController {
-    fn map_vc<F, R>(&mut self, mut mapper: F) -> Option<R>
+    fn map_vc<F, R>(&mut self, mapper: F) -> Option<R>
     where
         F: FnMut(&mut MenuController) -> R,
     {
-        if let Some(ref mut ctrl) = self.menu_controller {
-            Some(mapper(ctrl))
-        } else {
-            None
-        }
+        self.menu_controller.as_mut().map(mapper)
     }
 
     fn set_vc(&mut self, vc: MenuController) {
@@ -145,15 +225,11 @@ impl VCMapper<MenuController> for AppController {
 }
 
 impl VCMapper<CardsController> for AppController {
-    fn map_vc<F, R>(&mut self, mut mapper: F) -> Option<R>
+    fn map_vc<F, R>(&mut self, mapper: F) -> Option<R>
     where
         F: FnMut(&mut CardsController) -> R,
     {
-        if let Some(ref mut ctrl) = self.cards_controller {
-            Some(mapper(ctrl))
-        } else {
-            None
-        }
+        self.cards_controller.as_mut().map(mapper)
     }
 
     fn set_vc(&mut self, vc: CardsController) {
@@ -162,12 +238,27 @@ impl VCMapper<CardsController> for AppController {
     }
 }
 
+impl VCMapper<SummaryController> for AppController {
+    fn map_vc<F, R>(&mut self, mapper: F) -> Option<R>
+    where
+        F: FnMut(&mut SummaryController) -> R,
+    {
+        self.summary_controller.as_mut().map(mapper)
+    }
+
+    fn set_vc(&mut self, vc: SummaryController) {
+        assert!(self.summary_controller.is_none());
+        self.summary_controller = Some(vc);
+    }
+}
+
 impl AppController {
     fn new(content: Element) -> Rc<RefCell<Self>> {
         Rc::new(RefCell::new(Self {
             content,
             menu_controller: None,
             cards_controller: None,
+            summary_controller: None,
         }))
     }
 
@@ -182,8 +273,10 @@ impl AppController {
         match state {
             State::Menu => AppController::show_view_controller(self_, MenuController::default()),
             State::Settings => {}
-            State::Playing(game_type) => AppController::show_view_controller(self_, CardsController::new(game_type)),
-            State::Ended(_) => {}
+            State::Playing(game_type, categories, mode) => {
+                AppController::show_view_controller(self_, CardsController::new(game_type, categories, mode))
+            }
+            State::Ended(summary) => AppController::show_view_controller(self_, SummaryController::new(summary)),
         }
     }
 
@@ -202,6 +295,10 @@ impl AppController {
             sub_controller.hide();
         }
         controller.cards_controller = None;
+        if let Some(ref mut sub_controller) = controller.summary_controller {
+            sub_controller.hide();
+        }
+        controller.summary_controller = None;
         controller
             .content
             .append_with_node_1(&view_controller.show(presenter))
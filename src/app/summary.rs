@@ -0,0 +1,79 @@
+use crate::logic::GameSummary;
+
+use super::{log, Presenter, Reaction, State, ViewController};
+
+use web_sys::{window, Element};
+
+pub struct SummaryController {
+    summary: GameSummary,
+    view: Option<Element>,
+}
+
+impl SummaryController {
+    pub fn new(summary: GameSummary) -> Self {
+        Self { summary, view: None }
+    }
+}
+
+impl ViewController for SummaryController {
+    fn show(&mut self, mut presenter: Presenter) -> Element {
+        assert_eq!(self.view, None);
+
+        let document = window().unwrap().document().unwrap();
+
+        // create the summary view, tapping anywhere goes back to the menu
+        let view = document.create_element("div").expect("create_element failed");
+        view.set_class_name("summary");
+        self.view = Some(view.clone());
+
+        if let Some(letter_grade) = self.summary.letter_grade() {
+            let grade = document.create_element("div").expect("create_element failed");
+            grade.set_class_name("letter-grade");
+            grade.set_inner_html(&letter_grade.to_string());
+            view.append_with_node_1(&grade).expect("append_with_node_1 failed");
+        }
+
+        let details = document.create_element("div").expect("create_element failed");
+        details.set_class_name("details");
+        details.set_inner_html(&format!(
+            "{}/{} correct<br>{:.0}% accuracy<br>{}s",
+            self.summary.correct,
+            self.summary.answered,
+            self.summary.accuracy() * 100.0,
+            self.summary.duration.as_secs()
+        ));
+        view.append_with_node_1(&details).expect("append_with_node_1 failed");
+
+        // the mistake that ended the game didn't get a card to show its solution
+        if let Some(ref correction) = self.summary.correction {
+            let last = document.create_element("div").expect("create_element failed");
+            last.set_class_name("correction");
+            last.set_inner_html(&format!("&#10007; {}", correction));
+            view.append_with_node_1(&last).expect("append_with_node_1 failed");
+        }
+
+        // rating of every unit pair with its uncertainty
+        let ratings = document.create_element("div").expect("create_element failed");
+        ratings.set_class_name("ratings");
+        let lines: Vec<String> = self
+            .summary
+            .ratings
+            .iter()
+            .map(|((a, b), rating)| format!("{}/{}: {:.0} &plusmn; {:.0}", a, b, rating.value, rating.deviation))
+            .collect();
+        ratings.set_inner_html(&lines.join("<br>"));
+        view.append_with_node_1(&ratings).expect("append_with_node_1 failed");
+
+        presenter.add_event_reaction(&view, "click", Reaction::Transition(State::Menu));
+
+        view
+    }
+
+    fn hide(&mut self) {
+        log!("hiding summary");
+        if let Some(ref view) = self.view {
+            view.remove();
+        }
+        self.view = None;
+    }
+}
@@ -0,0 +1,483 @@
+use std::fmt;
+
+use rand::random;
+
+use super::{convert, Compound, Float, Notation, Substance, Unit};
+
+/// How values are spread over a context's range.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sampling {
+    /// Evenly.
+    Uniform,
+    /// Evenly over orders of magnitude, only for positive values.
+    LogUniform,
+}
+
+impl Sampling {
+    /// Position of `value` in the space where the sampling is even.
+    pub fn position_of(&self, value: Float) -> Float {
+        match self {
+            Sampling::Uniform => value,
+            Sampling::LogUniform => value.ln(),
+        }
+    }
+
+    pub fn value_at(&self, position: Float) -> Float {
+        match self {
+            Sampling::Uniform => position,
+            Sampling::LogUniform => position.exp(),
+        }
+    }
+
+    /// Random value between `min` and `max`.
+    pub fn sample(&self, min: Float, max: Float) -> Float {
+        let (low, high) = (self.position_of(min), self.position_of(max));
+        self.value_at(low + (high - low) * random::<Float>())
+    }
+
+    /// Smallest delta (in the sampling space) that keeps the values `delta` below and above `mid_point` at least
+    /// `relative_gap` apart.
+    pub fn min_delta(&self, mid_point: Float, relative_gap: Float) -> Float {
+        match self {
+            // the bigger value is at most |mid_point| + delta
+            Sampling::Uniform => relative_gap * mid_point.abs() / (2.0 - relative_gap),
+            // the values are e^(mid_point - delta) and e^(mid_point + delta)
+            Sampling::LogUniform => -(1.0 - relative_gap).ln() / 2.0,
+        }
+    }
+
+    /// How fine `value` has to be rounded to keep gaps of `half_gap`, log-uniform values can't be rounded down to zero
+    /// either.
+    pub fn resolution(&self, value: Float, half_gap: Float) -> Float {
+        match self {
+            Sampling::Uniform => half_gap,
+            Sampling::LogUniform => half_gap.min(value.abs()),
+        }
+    }
+}
+
+/// Everyday situation the values of a challenge come from, it keeps them in a realistic range.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Context {
+    // Temperature
+    Weather,
+    Oven,
+    BodyTemperature,
+    // Temperature difference
+    Warming,
+    Fever,
+    // Length
+    BodyHeight,
+    Room,
+    Walk,
+    Commute,
+    Sailing,
+    Flight,
+    Woodwork,
+    // Area
+    Apartment,
+    Garden,
+    Park,
+    Farm,
+    // Volume
+    Drink,
+    Recipe,
+    FuelTank,
+    Bath,
+    // Mass
+    BodyWeight,
+    Luggage,
+    Groceries,
+    Baby,
+    // Energy
+    Lifting,
+    CupOfTea,
+    // Pressure
+    Tyre,
+    BloodPressure,
+    // Time
+    Movie,
+    Marathon,
+    // Wind speed
+    Breeze,
+    Storm,
+    // Power
+    WiFi,
+    Bluetooth,
+    // Volume weighed
+    Flour,
+    Sugar,
+    Water,
+    // Concentration
+    BloodSugar,
+    Cholesterol,
+    // Extreme length
+    Molecule,
+    Cell,
+    SolarSystem,
+    OortCloud,
+    NearbyStars,
+    Galaxies,
+}
+
+const ALL_CONTEXTS: &[Context] = &[
+    Context::Weather,
+    Context::Oven,
+    Context::BodyTemperature,
+    Context::Warming,
+    Context::Fever,
+    Context::BodyHeight,
+    Context::Room,
+    Context::Walk,
+    Context::Commute,
+    Context::Sailing,
+    Context::Flight,
+    Context::Woodwork,
+    Context::Apartment,
+    Context::Garden,
+    Context::Park,
+    Context::Farm,
+    Context::Drink,
+    Context::Recipe,
+    Context::FuelTank,
+    Context::Bath,
+    Context::BodyWeight,
+    Context::Luggage,
+    Context::Groceries,
+    Context::Baby,
+    Context::Lifting,
+    Context::CupOfTea,
+    Context::Tyre,
+    Context::BloodPressure,
+    Context::Movie,
+    Context::Marathon,
+    Context::Breeze,
+    Context::Storm,
+    Context::WiFi,
+    Context::Bluetooth,
+    Context::Flour,
+    Context::Sugar,
+    Context::Water,
+    Context::BloodSugar,
+    Context::Cholesterol,
+    Context::Molecule,
+    Context::Cell,
+    Context::SolarSystem,
+    Context::OortCloud,
+    Context::NearbyStars,
+    Context::Galaxies,
+];
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Context::Weather => "weather",
+            Context::Oven => "oven",
+            Context::BodyTemperature => "body temperature",
+            Context::Warming => "warming over a day",
+            Context::Fever => "rise in fever",
+            Context::BodyHeight => "body height",
+            Context::Room => "room",
+            Context::Walk => "walk",
+            Context::Commute => "commute",
+            Context::Sailing => "sailing",
+            Context::Flight => "flight",
+            Context::Woodwork => "woodwork",
+            Context::Apartment => "apartment",
+            Context::Garden => "garden",
+            Context::Park => "park",
+            Context::Farm => "farm",
+            Context::Drink => "drink",
+            Context::Recipe => "recipe",
+            Context::FuelTank => "fuel tank",
+            Context::Bath => "bath",
+            Context::BodyWeight => "body weight",
+            Context::Luggage => "luggage",
+            Context::Groceries => "groceries",
+            Context::Baby => "newborn baby",
+            Context::Lifting => "lifting a bag",
+            Context::CupOfTea => "cup of tea",
+            Context::Tyre => "tyre",
+            Context::BloodPressure => "blood pressure",
+            Context::Movie => "movie",
+            Context::Marathon => "marathon",
+            Context::Breeze => "breeze",
+            Context::Storm => "storm",
+            Context::WiFi => "wi-fi router",
+            Context::Bluetooth => "bluetooth",
+            Context::Flour => "flour",
+            Context::Sugar => "sugar",
+            Context::Water => "water",
+            Context::BloodSugar => "blood sugar",
+            Context::Cholesterol => "cholesterol",
+            Context::Molecule => "molecule",
+            Context::Cell => "cell",
+            Context::SolarSystem => "solar system",
+            Context::OortCloud => "oort cloud",
+            Context::NearbyStars => "nearby stars",
+            Context::Galaxies => "galaxies",
+        })
+    }
+}
+
+impl Context {
+    /// Units the context makes sense for.
+    pub fn units(&self) -> &'static [Unit] {
+        match self {
+            Context::Weather | Context::Oven | Context::BodyTemperature => &[Unit::Fahrenheit, Unit::Celsius],
+            Context::Warming | Context::Fever => &[Unit::FahrenheitDifference, Unit::CelsiusDifference],
+            Context::BodyHeight => &[Unit::Centimeter, Unit::Foot, Unit::Meter],
+            Context::Room => &[Unit::Foot, Unit::Meter],
+            Context::Walk => &[Unit::Meter, Unit::Kilometer],
+            Context::Commute => &[Unit::Kilometer, Unit::Mile],
+            Context::Sailing => &[Unit::Kilometer, Unit::NauticalMile],
+            Context::Flight => &[Unit::Kilometer, Unit::Mile, Unit::NauticalMile],
+            Context::Woodwork => &[Unit::Centimeter, Unit::Inch],
+            Context::Apartment | Context::Garden => &[Unit::SquareFoot, Unit::SquareMeter],
+            Context::Park => &[Unit::SquareMeter, Unit::Acre, Unit::Hectare],
+            Context::Farm => &[Unit::Acre, Unit::Hectare],
+            Context::Drink => &[Unit::Millilitre, Unit::FluidOunce, Unit::Litre],
+            Context::Recipe => &[Unit::Millilitre, Unit::FluidOunce, Unit::Cup],
+            Context::FuelTank | Context::Bath => &[Unit::Litre, Unit::Gallon],
+            Context::BodyWeight => &[Unit::Pound, Unit::Kilogram, Unit::Stone],
+            Context::Luggage | Context::Baby => &[Unit::Pound, Unit::Kilogram],
+            Context::Groceries => &[Unit::Gram, Unit::Ounce, Unit::Pound, Unit::Kilogram],
+            Context::Lifting | Context::CupOfTea => &[Unit::Joule, Unit::Calorie],
+            Context::Tyre | Context::BloodPressure => &[Unit::Kilopascal, Unit::PoundPerSquareInch],
+            Context::Movie | Context::Marathon => &[Unit::Minute, Unit::Hour],
+            Context::Breeze | Context::Storm => &[Unit::KilometerPerHour, Unit::Knot, Unit::Beaufort],
+            Context::WiFi | Context::Bluetooth => &[Unit::DecibelMilliwatt, Unit::Milliwatt],
+            Context::Flour | Context::Sugar | Context::Water => &[Unit::Millilitre, Unit::Cup, Unit::Gram],
+            Context::BloodSugar | Context::Cholesterol => &[Unit::MilligramPerDecilitre, Unit::MillimolePerLitre],
+            Context::Molecule => &[Unit::Angstrom, Unit::Nanometer],
+            Context::Cell => &[Unit::Nanometer, Unit::Micrometer],
+            Context::SolarSystem => &[Unit::Kilometer, Unit::Au],
+            Context::OortCloud => &[Unit::Au, Unit::LightYear],
+            Context::NearbyStars | Context::Galaxies => &[Unit::LightYear, Unit::Parsec],
+        }
+    }
+
+    /// Realistic values in one of the context's units.
+    fn range(&self) -> (Unit, Float, Float) {
+        match self {
+            Context::Weather => (Unit::Celsius, -30.0, 45.0),
+            Context::Oven => (Unit::Celsius, 120.0, 250.0),
+            Context::BodyTemperature => (Unit::Celsius, 35.0, 42.0),
+            Context::Warming => (Unit::CelsiusDifference, 1.0, 20.0),
+            Context::Fever => (Unit::CelsiusDifference, 0.5, 4.0),
+            Context::BodyHeight => (Unit::Foot, 4.5, 7.0),
+            Context::Room => (Unit::Meter, 2.0, 15.0),
+            Context::Walk => (Unit::Meter, 100.0, 5000.0),
+            Context::Commute => (Unit::Kilometer, 1.0, 50.0),
+            Context::Sailing => (Unit::Kilometer, 5.0, 200.0),
+            Context::Flight => (Unit::Kilometer, 200.0, 10000.0),
+            Context::Woodwork => (Unit::Inch, 0.5, 48.0),
+            Context::Apartment => (Unit::SquareMeter, 20.0, 200.0),
+            Context::Garden => (Unit::SquareMeter, 10.0, 1000.0),
+            Context::Park => (Unit::Hectare, 0.5, 50.0),
+            Context::Farm => (Unit::Hectare, 10.0, 1000.0),
+            Context::Drink => (Unit::Millilitre, 100.0, 1000.0),
+            Context::Recipe => (Unit::Millilitre, 60.0, 750.0),
+            Context::FuelTank => (Unit::Litre, 30.0, 90.0),
+            Context::Bath => (Unit::Litre, 100.0, 300.0),
+            Context::BodyWeight => (Unit::Kilogram, 40.0, 150.0),
+            Context::Luggage => (Unit::Kilogram, 5.0, 32.0),
+            Context::Groceries => (Unit::Gram, 100.0, 5000.0),
+            Context::Baby => (Unit::Kilogram, 2.0, 5.0),
+            Context::Lifting => (Unit::Joule, 20.0, 500.0),
+            Context::CupOfTea => (Unit::Calorie, 10000.0, 25000.0),
+            Context::Tyre => (Unit::Kilopascal, 150.0, 350.0),
+            Context::BloodPressure => (Unit::Kilopascal, 10.0, 25.0),
+            Context::Movie => (Unit::Minute, 80.0, 200.0),
+            Context::Marathon => (Unit::Minute, 120.0, 360.0),
+            Context::Breeze => (Unit::Beaufort, 1.0, 6.0),
+            Context::Storm => (Unit::Beaufort, 6.0, 12.0),
+            Context::WiFi => (Unit::Milliwatt, 1.0, 200.0),
+            Context::Bluetooth => (Unit::Milliwatt, 0.1, 100.0),
+            Context::Flour => (Unit::Gram, 30.0, 600.0),
+            Context::Sugar => (Unit::Gram, 25.0, 500.0),
+            Context::Water => (Unit::Millilitre, 50.0, 1000.0),
+            Context::BloodSugar => (Unit::MillimolePerLitre, 3.0, 20.0),
+            Context::Cholesterol => (Unit::MillimolePerLitre, 2.0, 8.0),
+            Context::Molecule => (Unit::Angstrom, 0.5, 100.0),
+            Context::Cell => (Unit::Micrometer, 0.1, 100.0),
+            Context::SolarSystem => (Unit::Au, 0.3, 50.0),
+            Context::OortCloud => (Unit::Au, 2000.0, 100000.0),
+            Context::NearbyStars => (Unit::LightYear, 4.0, 1000.0),
+            Context::Galaxies => (Unit::Parsec, 8000.0, 2e7),
+        }
+    }
+
+    /// What is being measured when conversions depend on it, e.g. cups of flour weigh less than cups of water.
+    pub fn substance(&self) -> Option<Substance> {
+        match self {
+            Context::Flour => Some(Substance::Flour),
+            Context::Sugar => Some(Substance::Sugar),
+            Context::Water => Some(Substance::Water),
+            Context::BloodSugar => Some(Substance::Glucose),
+            Context::Cholesterol => Some(Substance::Cholesterol),
+            _ => None,
+        }
+    }
+
+    /// Converts `value` like `convert` does, falling back to the context's substance for conversions that depend on
+    /// it.
+    pub fn convert(&self, value: Float, from: Unit, to: Unit) -> Option<Float> {
+        convert(value, from, to).or_else(|| self.substance()?.convert(value, from, to))
+    }
+
+    /// Smallest and biggest realistic value in `unit`.
+    pub fn range_in(&self, unit: Unit) -> (Float, Float) {
+        let (range_unit, min, max) = self.range();
+        (
+            self.convert(min, range_unit, unit).expect("unit outside of context"),
+            self.convert(max, range_unit, unit).expect("unit outside of context"),
+        )
+    }
+
+    /// How values of `unit` are written, e.g. body height in feet and inches or recipes in quarter cups.
+    pub fn notation(&self, unit: Unit) -> Notation {
+        match (self, unit) {
+            (Context::BodyHeight, Unit::Foot) => Notation::Compound(Compound::FeetInches),
+            (Context::Baby, Unit::Pound) => Notation::Compound(Compound::PoundsOunces),
+            (Context::BodyWeight, Unit::Stone) => Notation::Compound(Compound::StonesPounds),
+            (Context::Movie, Unit::Hour) | (Context::Marathon, Unit::Hour) => {
+                Notation::Compound(Compound::HoursMinutes)
+            }
+            (Context::Woodwork, Unit::Inch) => Notation::Fraction { denominator: 16 },
+            (Context::Recipe, Unit::Cup) | (Context::Flour, Unit::Cup) | (Context::Sugar, Unit::Cup) => {
+                Notation::Fraction { denominator: 4 }
+            }
+            (Context::Molecule, _) | (Context::Cell, _) => Notation::Scientific { engineering: true },
+            (Context::SolarSystem, _)
+            | (Context::OortCloud, _)
+            | (Context::NearbyStars, _)
+            | (Context::Galaxies, _) => Notation::Scientific { engineering: false },
+            _ => Notation::Decimal,
+        }
+    }
+
+    /// Rounding step for values of `unit` that have to keep differences of `resolution`. Compound and fractional
+    /// values can't go below their smallest part, scientific ones go by powers of ten and decimals get decimal places
+    /// up to the unit's precision.
+    pub fn step(&self, unit: Unit, resolution: Float) -> Float {
+        self.notation(unit)
+            .step(resolution)
+            .unwrap_or_else(|| (10.0 as Float).powi(-(unit.decimals_for(resolution) as i32)))
+    }
+
+    pub fn sampling(&self) -> Sampling {
+        self.units()[0].quantity().sampling()
+    }
+
+    /// Smallest delta (in the sampling space of `unit`) that keeps the values `delta` below and above `mid_point` at
+    /// least `relative_gap` apart, measured against the range for scales with an arbitrary zero.
+    pub fn min_delta(&self, unit: Unit, mid_point: Float, relative_gap: Float) -> Float {
+        if unit.quantity().has_arbitrary_zero() {
+            let (min, max) = self.range_in(unit);
+            relative_gap * (max - min) / 2.0
+        } else {
+            self.sampling().min_delta(mid_point, relative_gap)
+        }
+    }
+
+    /// Range of `unit` mapped to the sampling space.
+    pub fn space_range_in(&self, unit: Unit) -> (Float, Float) {
+        let (min, max) = self.range_in(unit);
+        (self.sampling().position_of(min), self.sampling().position_of(max))
+    }
+
+    /// Contexts that make sense for all of `units`. Substance contexts are left out for units that convert without
+    /// the substance, "flour" on millilitres and cups would hint at something that doesn't matter, unless nothing else
+    /// fits (e.g. a lone mg/dL).
+    pub fn all_for(units: &[Unit]) -> Vec<Context> {
+        let needs_substance = units
+            .iter()
+            .any(|a| units.iter().any(|b| convert(1.0, *a, *b).is_none()));
+        let fitting: Vec<Context> = ALL_CONTEXTS
+            .iter()
+            .copied()
+            .filter(|context| units.iter().all(|unit| context.units().contains(unit)))
+            .collect();
+        if needs_substance || fitting.iter().all(|context| context.substance().is_some()) {
+            fitting
+        } else {
+            fitting
+                .into_iter()
+                .filter(|context| context.substance().is_none())
+                .collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{ALL_CATEGORIES, ALL_UNITS};
+    use super::*;
+
+    #[test]
+    fn every_unit_pair_has_a_context() {
+        for unit in ALL_UNITS {
+            for (a, b) in ALL_CATEGORIES
+                .iter()
+                .flat_map(|category| unit.quantity().unit_pairs_in(*category))
+            {
+                let contexts = Context::all_for(&[a, b]);
+                assert!(!contexts.is_empty(), "{:?}", (a, b));
+                for context in contexts {
+                    let (min, max) = context.range_in(a);
+                    assert!(min < max, "{:?}", context);
+                    // only the bigger unit is sampled, logarithmic ones can be negative
+                    let (low, high) = context.space_range_in(b);
+                    assert!(low.is_finite() && high.is_finite() && low < high, "{:?}", context);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn substances_only_hint_where_they_matter() {
+        for unit in ALL_UNITS {
+            for (a, b) in ALL_CATEGORIES
+                .iter()
+                .flat_map(|category| unit.quantity().unit_pairs_in(*category))
+            {
+                let contexts = Context::all_for(&[a, b]);
+                let with_substance = contexts.iter().any(|context| context.substance().is_some());
+                assert_eq!(
+                    with_substance,
+                    convert(1.0, a, b).is_none(),
+                    "{:?}: {:?}",
+                    (a, b),
+                    contexts
+                );
+            }
+        }
+        assert_eq!(Context::all_for(&[Unit::Millilitre, Unit::Cup]), vec![Context::Recipe]);
+    }
+
+    #[test]
+    fn log_uniform_sampling_spreads_magnitudes() {
+        let below_tenth = (0..1000)
+            .map(|_| Sampling::LogUniform.sample(1.0, 1000.0))
+            .filter(|value| *value < 100.0)
+            .count();
+        assert!(below_tenth > 600, "{}", below_tenth);
+        let delta = Sampling::LogUniform.min_delta(5.0, 0.1);
+        let (a, b) = ((5.0 - delta).exp(), (5.0 + delta).exp());
+        assert!(((b - a) / b - 0.1).abs() < 0.001);
+    }
+
+    #[test]
+    fn ranges_are_converted() {
+        assert_eq!(Context::Oven.range_in(Unit::Celsius), (120.0, 250.0));
+        assert_eq!(Context::Oven.range_in(Unit::Fahrenheit), (248.0, 482.0));
+        let (_, max) = Context::Flight.range_in(Unit::NauticalMile);
+        assert!((max - 5399.6).abs() < 0.1, "{}", max);
+        assert_eq!(
+            Context::all_for(&[Unit::Kilometer, Unit::Mile, Unit::NauticalMile]),
+            vec![Context::Flight]
+        );
+        assert!(Context::all_for(&[Unit::Celsius]).contains(&Context::Weather));
+    }
+}
@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::{Context, Float, Quantity, Unit};
+
+/// Maps the level of a challenge to how far apart its values are.
+pub trait DifficultyCurve: fmt::Debug {
+    /// Gap between the winner and the mid point in `context` at `level` (fractional, 0 is the easiest). It's measured
+    /// in the context's sampling space of `unit`, so for log-uniform contexts it's the log of the ratio.
+    fn delta(&self, unit: Unit, context: Context, level: Float) -> Float;
+}
+
+/// Starts at a fraction of the context's range (in its sampling space) and shrinks by `ratio` with every level until
+/// it reaches `min_delta`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GeometricCurve {
+    /// Delta at level 0 as a fraction of the context's range.
+    pub start: Float,
+    pub ratio: Float,
+    /// Smallest delta, in units for uniform sampling (below 1 the values get decimal places) and as a log ratio for
+    /// log-uniform sampling (0.01 is about 1%).
+    pub min_delta: Float,
+}
+
+impl Default for GeometricCurve {
+    fn default() -> Self {
+        Self {
+            start: 1.0 / 2.1, // slightly less than half
+            ratio: 0.9,
+            min_delta: 0.01,
+        }
+    }
+}
+
+impl DifficultyCurve for GeometricCurve {
+    fn delta(&self, unit: Unit, context: Context, level: Float) -> Float {
+        let (low, high) = context.space_range_in(unit);
+        ((high - low) * self.start * self.ratio.powf(level)).max(self.min_delta)
+    }
+}
+
+/// Uses a separate curve for some quantities and the default one for the rest.
+#[derive(Debug)]
+pub struct QuantityCurves {
+    default: Box<dyn DifficultyCurve>,
+    overrides: HashMap<Quantity, Box<dyn DifficultyCurve>>,
+}
+
+impl QuantityCurves {
+    pub fn new(default: Box<dyn DifficultyCurve>) -> Self {
+        Self {
+            default,
+            overrides: HashMap::new(),
+        }
+    }
+
+    pub fn with_override(mut self, quantity: Quantity, curve: Box<dyn DifficultyCurve>) -> Self {
+        self.overrides.insert(quantity, curve);
+        self
+    }
+}
+
+impl DifficultyCurve for QuantityCurves {
+    fn delta(&self, unit: Unit, context: Context, level: Float) -> Float {
+        self.overrides
+            .get(&unit.quantity())
+            .unwrap_or(&self.default)
+            .delta(unit, context, level)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn geometric_curve_shrinks_down_to_min_delta() {
+        let curve = GeometricCurve::default();
+        let level0 = curve.delta(Unit::Celsius, Context::Weather, 0.0);
+        assert!((level0 - 75.0 / 2.1).abs() < 0.001);
+        assert!(curve.delta(Unit::Celsius, Context::Weather, 1.0) < level0);
+        assert!(curve.delta(Unit::Celsius, Context::Weather, 50.0) < 1.0);
+        assert_eq!(curve.delta(Unit::Celsius, Context::Weather, 1000.0), 0.01);
+
+        let whole = GeometricCurve {
+            min_delta: 1.0,
+            ..GeometricCurve::default()
+        };
+        assert_eq!(whole.delta(Unit::Celsius, Context::Weather, 50.0), 1.0);
+    }
+
+    #[test]
+    fn overrides_apply_to_their_quantity_only() {
+        let curves = QuantityCurves::new(Box::new(GeometricCurve::default())).with_override(
+            Quantity::Temperature,
+            Box::new(GeometricCurve {
+                start: 0.1,
+                ..GeometricCurve::default()
+            }),
+        );
+        assert!((curves.delta(Unit::Celsius, Context::Weather, 0.0) - 7.5).abs() < 0.001);
+        assert_eq!(
+            curves.delta(Unit::Meter, Context::Room, 3.0),
+            GeometricCurve::default().delta(Unit::Meter, Context::Room, 3.0)
+        );
+    }
+}
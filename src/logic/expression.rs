@@ -0,0 +1,128 @@
+use std::fmt;
+
+use rand::{random, Rng};
+
+use super::{convert, round_to, Float, Quantity, Unit};
+
+/// Small calculation a choice's value is made of, e.g. "3 ft + 20 in" or "2 × 5 km".
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Expression {
+    /// Sum of two measurements, usually a unit and its subunit.
+    Sum((Unit, Float), (Unit, Float)),
+    /// Measurement taken `factor` times.
+    Multiple(u32, (Unit, Float)),
+}
+
+impl fmt::Display for Expression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let term = |(unit, value): (Unit, Float)| format!("{} {}", unit.format_value(value), unit);
+        match self {
+            Expression::Sum(first, second) => write!(f, "{} + {}", term(*first), term(*second)),
+            Expression::Multiple(factor, measurement) => write!(f, "{} × {}", factor, term(*measurement)),
+        }
+    }
+}
+
+impl Expression {
+    /// Value of the whole expression in `unit`, none if one of its terms can't be converted.
+    pub fn evaluate(&self, unit: Unit) -> Option<Float> {
+        match self {
+            Expression::Sum((a, a_value), (b, b_value)) => {
+                Some(convert(*a_value, *a, unit)? + convert(*b_value, *b, unit)?)
+            }
+            Expression::Multiple(factor, (a, value)) => convert(*factor as Float * value, *a, unit),
+        }
+    }
+
+    /// Random expression close to `value` of `unit` with terms written in whole steps of their units' precision,
+    /// none for quantities that can't be added up (e.g. temperatures) or values too small to split.
+    pub fn near(unit: Unit, value: Float) -> Option<Self> {
+        if !is_additive(unit.quantity()) {
+            return None;
+        }
+        let step = |unit: Unit| (10.0 as Float).powi(-(unit.precision() as i32));
+        match subunit(unit) {
+            Some(subunit) if random::<bool>() => {
+                // the bigger part stays in the unit, the rest goes to the subunit
+                let whole = (value * rand::thread_rng().gen_range(0.4..0.8)).floor();
+                let rest = round_to(convert(value - whole, unit, subunit)?, step(subunit), Float::round);
+                if whole < 1.0 || rest <= 0.0 {
+                    return None;
+                }
+                Some(Expression::Sum((unit, whole), (subunit, rest)))
+            }
+            _ => {
+                let factor = rand::thread_rng().gen_range(2..=5);
+                let part = round_to(value / factor as Float, step(unit), Float::round);
+                if part <= 0.0 {
+                    return None;
+                }
+                Some(Expression::Multiple(factor, (unit, part)))
+            }
+        }
+    }
+}
+
+/// Quantities whose measurements still make sense when added up.
+fn is_additive(quantity: Quantity) -> bool {
+    match quantity {
+        Quantity::TemperatureDifference
+        | Quantity::Length
+        | Quantity::Area
+        | Quantity::Volume
+        | Quantity::Mass
+        | Quantity::Energy
+        | Quantity::Time => true,
+        Quantity::Temperature
+        | Quantity::Pressure
+        | Quantity::WindSpeed
+        | Quantity::Power
+        | Quantity::Concentration
+        | Quantity::ExtremeLength => false,
+    }
+}
+
+/// Smaller unit of the same system that `unit` is commonly split into.
+fn subunit(unit: Unit) -> Option<Unit> {
+    match unit {
+        Unit::Foot => Some(Unit::Inch),
+        Unit::Meter => Some(Unit::Centimeter),
+        Unit::Kilometer => Some(Unit::Meter),
+        Unit::Hectare => Some(Unit::SquareMeter),
+        Unit::Cup => Some(Unit::FluidOunce),
+        Unit::Litre => Some(Unit::Millilitre),
+        Unit::Pound => Some(Unit::Ounce),
+        Unit::Kilogram => Some(Unit::Gram),
+        Unit::Stone => Some(Unit::Pound),
+        Unit::Hour => Some(Unit::Minute),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expressions_are_evaluated_and_written() {
+        let sum = Expression::Sum((Unit::Foot, 3.0), (Unit::Inch, 20.0));
+        assert_eq!(sum.to_string(), "3 ft + 20 in");
+        assert!((sum.evaluate(Unit::Foot).unwrap() - 14.0 / 3.0).abs() < 0.001);
+        assert!((sum.evaluate(Unit::Centimeter).unwrap() - 142.24).abs() < 0.001);
+
+        let multiple = Expression::Multiple(2, (Unit::Kilometer, 5.0));
+        assert_eq!(multiple.to_string(), "2 × 5 km");
+        assert!((multiple.evaluate(Unit::Mile).unwrap() - 6.2137).abs() < 0.001);
+        assert_eq!(multiple.evaluate(Unit::Kilogram), None);
+    }
+
+    #[test]
+    fn expressions_stay_close_to_the_value() {
+        assert_eq!(Expression::near(Unit::Celsius, 20.0), None);
+        for _ in 0..100 {
+            let expression = Expression::near(Unit::Foot, 5.5).unwrap();
+            let value = expression.evaluate(Unit::Foot).unwrap();
+            assert!((value - 5.5).abs() < 0.15, "{}", expression);
+        }
+    }
+}
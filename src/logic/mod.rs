@@ -0,0 +1,2940 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::Duration;
+
+use rand::seq::SliceRandom;
+use rand::{random, Rng};
+
+pub use context::{Context, Sampling};
+pub use curve::{DifficultyCurve, GeometricCurve, QuantityCurves};
+pub use expression::Expression;
+pub use notation::{Compound, Notation};
+pub use rating::Rating;
+pub use scale::Scale;
+pub use scheduler::{PairScheduler, UniformScheduler, WeightedScheduler};
+pub use substance::Substance;
+
+mod context;
+mod curve;
+mod expression;
+mod notation;
+mod rating;
+mod scale;
+mod scheduler;
+mod substance;
+
+type Float = f32;
+type Level = usize;
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Quantity {
+    Temperature,
+    /// Change of temperature, e.g. warming by 10 C, which converts without the offset of the scales.
+    TemperatureDifference,
+    Length,
+    Area,
+    Volume,
+    Mass,
+    Energy,
+    Pressure,
+    Time,
+    WindSpeed,
+    Power,
+    /// Amount of a substance in blood, e.g. glucose, which converts by the substance's molar mass.
+    Concentration,
+    /// Lengths far from everyday ones, from molecules to galaxies, written in scientific notation.
+    ExtremeLength,
+}
+
+impl fmt::Display for Quantity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Quantity::Temperature => "temperature",
+            Quantity::TemperatureDifference => "temperature difference",
+            Quantity::Length => "length",
+            Quantity::Area => "area",
+            Quantity::Volume => "volume",
+            Quantity::Mass => "mass",
+            Quantity::Energy => "energy",
+            Quantity::Pressure => "pressure",
+            Quantity::Time => "time",
+            Quantity::WindSpeed => "wind speed",
+            Quantity::Power => "power",
+            Quantity::Concentration => "concentration",
+            Quantity::ExtremeLength => "scale",
+        })
+    }
+}
+
+/// Group of unit pairs that can be drilled on its own, each pair still keeps its own rating.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Category {
+    /// Units of different systems, e.g. km and mi.
+    Conversion,
+    /// Units of the same system, e.g. mL and L or oz and lb, drilling prefixes and sub-units.
+    WithinSystem,
+    /// Units that only convert for a given substance, e.g. cups and grams of flour.
+    Substance,
+}
+
+#[cfg(test)]
+const ALL_CATEGORIES: &[Category] = &[Category::Conversion, Category::WithinSystem, Category::Substance];
+
+/// Note: Units are ordered by their relative delta. That is a difference of a Fahrenheit degree is smaller than
+/// a difference of a Celsius degree or a foot is smaller than a meter and so on.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Unit {
+    // Temperature
+    Fahrenheit,
+    Celsius,
+
+    // Temperature difference
+    FahrenheitDifference,
+    CelsiusDifference,
+
+    // Length
+    Centimeter,
+    Inch,
+    Foot,
+    Meter,
+    Kilometer,
+    Mile,
+    NauticalMile,
+
+    // Area
+    SquareFoot,
+    SquareMeter,
+    Acre,
+    Hectare,
+
+    // Volume
+    Millilitre,
+    FluidOunce, // US
+    Cup,        // US
+    Litre,
+    Gallon, // US
+
+    // Mass
+    Gram,
+    Ounce,
+    Pound,
+    Kilogram,
+    Stone,
+
+    // Energy
+    Joule,
+    Calorie,
+
+    // Pressure
+    Kilopascal,
+    PoundPerSquareInch,
+
+    // Time
+    Minute,
+    Hour,
+
+    // Wind speed
+    KilometerPerHour,
+    Knot,
+    Beaufort,
+
+    // Power
+    DecibelMilliwatt,
+    Milliwatt,
+
+    // Concentration
+    MilligramPerDecilitre,
+    MillimolePerLitre,
+
+    // Extreme length
+    Angstrom,
+    Nanometer,
+    Micrometer,
+    Au,
+    LightYear,
+    Parsec,
+}
+
+#[cfg(test)]
+const ALL_UNITS: &[Unit] = &[
+    // Temperature
+    Unit::Fahrenheit,
+    Unit::Celsius,
+    // Temperature difference
+    Unit::FahrenheitDifference,
+    Unit::CelsiusDifference,
+    // Length
+    Unit::Centimeter,
+    Unit::Inch,
+    Unit::Foot,
+    Unit::Meter,
+    Unit::Kilometer,
+    Unit::Mile,
+    Unit::NauticalMile,
+    // Area
+    Unit::SquareFoot,
+    Unit::SquareMeter,
+    Unit::Acre,
+    Unit::Hectare,
+    // Volume
+    Unit::Millilitre,
+    Unit::FluidOunce,
+    Unit::Cup,
+    Unit::Litre,
+    Unit::Gallon,
+    // Mass
+    Unit::Gram,
+    Unit::Ounce,
+    Unit::Pound,
+    Unit::Kilogram,
+    Unit::Stone,
+    // Energy
+    Unit::Joule,
+    Unit::Calorie,
+    // Pressure
+    Unit::Kilopascal,
+    Unit::PoundPerSquareInch,
+    // Time
+    Unit::Minute,
+    Unit::Hour,
+    // Wind speed
+    Unit::KilometerPerHour,
+    Unit::Knot,
+    Unit::Beaufort,
+    // Power
+    Unit::DecibelMilliwatt,
+    Unit::Milliwatt,
+    // Concentration
+    Unit::MilligramPerDecilitre,
+    Unit::MillimolePerLitre,
+    // Extreme length
+    Unit::Angstrom,
+    Unit::Nanometer,
+    Unit::Micrometer,
+    Unit::Au,
+    Unit::LightYear,
+    Unit::Parsec,
+];
+
+impl fmt::Display for Unit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Unit::Fahrenheit => "F",
+            Unit::Celsius => "C",
+            Unit::FahrenheitDifference => "ΔF",
+            Unit::CelsiusDifference => "ΔC",
+            Unit::Centimeter => "cm",
+            Unit::Inch => "in",
+            Unit::Foot => "ft",
+            Unit::Meter => "m",
+            Unit::Kilometer => "km",
+            Unit::Mile => "mi",
+            Unit::NauticalMile => "NM",
+            Unit::SquareFoot => "sq ft",
+            Unit::SquareMeter => "m^2",
+            Unit::Acre => "acre",
+            Unit::Hectare => "ha",
+            Unit::Millilitre => "mL",
+            Unit::FluidOunce => "fl oz",
+            Unit::Cup => "cup",
+            Unit::Litre => "L",
+            Unit::Gallon => "gal",
+            Unit::Gram => "g",
+            Unit::Ounce => "oz",
+            Unit::Pound => "lb",
+            Unit::Kilogram => "kg",
+            Unit::Stone => "st",
+            Unit::Joule => "J",
+            Unit::Calorie => "cal",
+            Unit::Kilopascal => "kPa",
+            Unit::PoundPerSquareInch => "psi",
+            Unit::Minute => "min",
+            Unit::Hour => "h",
+            Unit::KilometerPerHour => "km/h",
+            Unit::Knot => "kn",
+            Unit::Beaufort => "Bft",
+            Unit::DecibelMilliwatt => "dBm",
+            Unit::Milliwatt => "mW",
+            Unit::MilligramPerDecilitre => "mg/dL",
+            Unit::MillimolePerLitre => "mmol/L",
+            Unit::Angstrom => "Å",
+            Unit::Nanometer => "nm",
+            Unit::Micrometer => "µm",
+            Unit::Au => "AU",
+            Unit::LightYear => "ly",
+            Unit::Parsec => "pc",
+        })
+    }
+}
+
+impl Unit {
+    pub fn quantity(&self) -> Quantity {
+        match self {
+            Unit::Fahrenheit | Unit::Celsius => Quantity::Temperature,
+            Unit::FahrenheitDifference | Unit::CelsiusDifference => Quantity::TemperatureDifference,
+            Unit::Centimeter
+            | Unit::Inch
+            | Unit::Foot
+            | Unit::Meter
+            | Unit::Kilometer
+            | Unit::Mile
+            | Unit::NauticalMile => Quantity::Length,
+            Unit::SquareFoot | Unit::SquareMeter | Unit::Acre | Unit::Hectare => Quantity::Area,
+            Unit::Millilitre | Unit::FluidOunce | Unit::Cup | Unit::Litre | Unit::Gallon => Quantity::Volume,
+            Unit::Gram | Unit::Ounce | Unit::Pound | Unit::Kilogram | Unit::Stone => Quantity::Mass,
+            Unit::Joule | Unit::Calorie => Quantity::Energy,
+            Unit::Kilopascal | Unit::PoundPerSquareInch => Quantity::Pressure,
+            Unit::Minute | Unit::Hour => Quantity::Time,
+            Unit::KilometerPerHour | Unit::Knot | Unit::Beaufort => Quantity::WindSpeed,
+            Unit::DecibelMilliwatt | Unit::Milliwatt => Quantity::Power,
+            Unit::MilligramPerDecilitre | Unit::MillimolePerLitre => Quantity::Concentration,
+            Unit::Angstrom | Unit::Nanometer | Unit::Micrometer | Unit::Au | Unit::LightYear | Unit::Parsec => {
+                Quantity::ExtremeLength
+            }
+        }
+    }
+
+    fn pair_with(self, other: Self) -> (Self, Self) {
+        if self < other {
+            (self, other)
+        } else {
+            (other, self)
+        }
+    }
+
+    /// Logarithmic units measure ratios in decibels, a single step of them is a big relative change.
+    pub fn is_logarithmic(&self) -> bool {
+        matches!(self, Unit::DecibelMilliwatt)
+    }
+
+    /// Table the unit is defined by instead of a formula, if any.
+    fn scale(&self) -> Option<Scale> {
+        match self {
+            Unit::Beaufort => Some(scale::BEAUFORT),
+            _ => None,
+        }
+    }
+
+    /// Most decimal places shown for values of the unit, finer differences aren't worth telling apart.
+    pub fn precision(&self) -> usize {
+        match self {
+            Unit::Centimeter
+            | Unit::SquareFoot
+            | Unit::Millilitre
+            | Unit::Gram
+            | Unit::Joule
+            | Unit::Minute
+            | Unit::KilometerPerHour
+            | Unit::Knot
+            | Unit::Beaufort
+            | Unit::MilligramPerDecilitre
+            | Unit::Nanometer => 0,
+            Unit::Fahrenheit
+            | Unit::Celsius
+            | Unit::FahrenheitDifference
+            | Unit::CelsiusDifference
+            | Unit::Inch
+            | Unit::Foot
+            | Unit::SquareMeter
+            | Unit::FluidOunce
+            | Unit::Ounce
+            | Unit::Pound
+            | Unit::Stone
+            | Unit::Calorie
+            | Unit::Kilopascal
+            | Unit::PoundPerSquareInch
+            | Unit::DecibelMilliwatt
+            | Unit::MillimolePerLitre
+            | Unit::Angstrom => 1,
+            Unit::Meter
+            | Unit::Kilometer
+            | Unit::Mile
+            | Unit::NauticalMile
+            | Unit::Acre
+            | Unit::Hectare
+            | Unit::Cup
+            | Unit::Litre
+            | Unit::Gallon
+            | Unit::Kilogram
+            | Unit::Hour
+            | Unit::Milliwatt
+            | Unit::Micrometer
+            | Unit::Au
+            | Unit::LightYear
+            | Unit::Parsec => 2,
+        }
+    }
+
+    /// Formats `value` with at most the unit's precision and without trailing zeros, e.g. "21.5" or "30".
+    pub fn format_value(&self, value: Float) -> String {
+        let formatted = format!("{:.*}", self.precision(), value);
+        let trimmed = if formatted.contains('.') {
+            formatted.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            &formatted
+        };
+        match trimmed {
+            "-0" => "0".to_string(),
+            _ => trimmed.to_string(),
+        }
+    }
+
+    /// Fewest decimal places (up to the unit's precision) at which values `delta` apart still differ by a whole step.
+    fn decimals_for(&self, delta: Float) -> usize {
+        (0..self.precision())
+            .find(|decimals| delta * (10.0 as Float).powi(*decimals as i32) >= 1.0)
+            .unwrap_or_else(|| self.precision())
+    }
+}
+
+fn convert(value: Float, from: Unit, to: Unit) -> Option<Float> {
+    match (from, to) {
+        (x, y) if x == y => Some(value),
+
+        (Unit::Celsius, Unit::Fahrenheit) => Some(value * 1.8 + 32.0),
+        (Unit::Fahrenheit, Unit::Celsius) => Some((value - 32.0) / 1.8),
+        (Unit::CelsiusDifference, Unit::FahrenheitDifference) => delta_convert(value, Unit::Celsius, Unit::Fahrenheit),
+        (Unit::FahrenheitDifference, Unit::CelsiusDifference) => delta_convert(value, Unit::Fahrenheit, Unit::Celsius),
+
+        (Unit::Centimeter, Unit::Inch) => Some(value / 2.54),
+        (Unit::Inch, Unit::Centimeter) => Some(value * 2.54),
+        (Unit::Inch, Unit::Foot) => Some(value / 12.0),
+        (Unit::Foot, Unit::Inch) => Some(value * 12.0),
+        (Unit::Centimeter, Unit::Meter) => Some(value / 100.0),
+        (Unit::Meter, Unit::Centimeter) => Some(value * 100.0),
+        (Unit::Centimeter, Unit::Foot) => Some(value / 30.48),
+        (Unit::Foot, Unit::Centimeter) => Some(value * 30.48),
+        (Unit::Meter, Unit::Foot) => Some(value / 0.3048),
+        (Unit::Foot, Unit::Meter) => Some(value * 0.3048),
+
+        (Unit::Meter, Unit::Kilometer) => Some(value / 1000.0),
+        (Unit::Kilometer, Unit::Meter) => Some(value * 1000.0),
+        (Unit::Kilometer, Unit::NauticalMile) => Some(value / 1.852),
+        (Unit::NauticalMile, Unit::Kilometer) => Some(value * 1.852),
+        (Unit::Mile, Unit::Kilometer) => Some(value * 1.609344),
+        (Unit::Kilometer, Unit::Mile) => Some(value / 1.609344),
+        (Unit::Mile, Unit::NauticalMile) => convert(
+            convert(value, Unit::Mile, Unit::Kilometer).unwrap(),
+            Unit::Kilometer,
+            Unit::NauticalMile,
+        ),
+        (Unit::NauticalMile, Unit::Mile) => convert(
+            convert(value, Unit::NauticalMile, Unit::Kilometer).unwrap(),
+            Unit::Kilometer,
+            Unit::Mile,
+        ),
+
+        (Unit::SquareFoot, Unit::SquareMeter) => Some(value * 0.09290341),
+        (Unit::SquareMeter, Unit::SquareFoot) => Some(value / 0.09290341),
+
+        (Unit::SquareMeter, Unit::Hectare) => Some(value / 10000.0),
+        (Unit::Hectare, Unit::SquareMeter) => Some(value * 10000.0),
+        (Unit::Hectare, Unit::Acre) => Some(value * 2.4711),
+        (Unit::Acre, Unit::Hectare) => Some(value / 2.4711),
+
+        (Unit::Millilitre, Unit::FluidOunce) => Some(value / 29.5735295625),
+        (Unit::FluidOunce, Unit::Millilitre) => Some(value * 29.5735295625),
+        (Unit::Cup, Unit::Millilitre) => Some(value * 236.58824),
+        (Unit::Millilitre, Unit::Cup) => Some(value / 236.58824),
+        (Unit::Cup, Unit::FluidOunce) => Some(value * 8.0),
+        (Unit::FluidOunce, Unit::Cup) => Some(value / 8.0),
+
+        (Unit::Millilitre, Unit::Litre) => Some(value / 1000.0),
+        (Unit::Litre, Unit::Millilitre) => Some(value * 1000.0),
+        (Unit::Gallon, Unit::Litre) => Some(value * 3.785411784),
+        (Unit::Litre, Unit::Gallon) => Some(value / 3.785411784),
+
+        (Unit::Pound, Unit::Kilogram) => Some(value * 0.45359237),
+        (Unit::Kilogram, Unit::Pound) => Some(value / 0.45359237),
+        (Unit::Gram, Unit::Kilogram) => Some(value / 1000.0),
+        (Unit::Kilogram, Unit::Gram) => Some(value * 1000.0),
+        (Unit::Ounce, Unit::Pound) => Some(value / 16.0),
+        (Unit::Pound, Unit::Ounce) => Some(value * 16.0),
+        (Unit::Gram, Unit::Ounce) | (Unit::Gram, Unit::Pound) => {
+            convert(convert(value, Unit::Gram, Unit::Kilogram).unwrap(), Unit::Kilogram, to)
+        }
+        (Unit::Ounce, Unit::Gram) | (Unit::Pound, Unit::Gram) => convert(
+            convert(value, from, Unit::Kilogram).unwrap(),
+            Unit::Kilogram,
+            Unit::Gram,
+        ),
+        (Unit::Ounce, Unit::Kilogram) => convert(
+            convert(value, Unit::Ounce, Unit::Pound).unwrap(),
+            Unit::Pound,
+            Unit::Kilogram,
+        ),
+        (Unit::Kilogram, Unit::Ounce) => convert(
+            convert(value, Unit::Kilogram, Unit::Pound).unwrap(),
+            Unit::Pound,
+            Unit::Ounce,
+        ),
+        (Unit::Stone, Unit::Pound) => Some(value * 14.0),
+        (Unit::Pound, Unit::Stone) => Some(value / 14.0),
+        (Unit::Stone, Unit::Kilogram) => convert(
+            convert(value, Unit::Stone, Unit::Pound).unwrap(),
+            Unit::Pound,
+            Unit::Kilogram,
+        ),
+        (Unit::Kilogram, Unit::Stone) => convert(
+            convert(value, Unit::Kilogram, Unit::Pound).unwrap(),
+            Unit::Pound,
+            Unit::Stone,
+        ),
+
+        (Unit::Calorie, Unit::Joule) => Some(value * 4.184),
+        (Unit::Joule, Unit::Calorie) => Some(value / 4.184),
+
+        (Unit::PoundPerSquareInch, Unit::Kilopascal) => Some(value * 6.894757),
+        (Unit::Kilopascal, Unit::PoundPerSquareInch) => Some(value / 6.894757),
+
+        (Unit::Hour, Unit::Minute) => Some(value * 60.0),
+        (Unit::Minute, Unit::Hour) => Some(value / 60.0),
+
+        (Unit::KilometerPerHour, Unit::Knot) => Some(value / 1.852),
+        (Unit::Knot, Unit::KilometerPerHour) => Some(value * 1.852),
+        (Unit::Knot, Unit::Beaufort) => Some(scale::BEAUFORT.position_of(value)),
+        (Unit::Beaufort, Unit::Knot) => Some(scale::BEAUFORT.base_value_at(value)),
+        (Unit::KilometerPerHour, Unit::Beaufort) => convert(
+            convert(value, Unit::KilometerPerHour, Unit::Knot).unwrap(),
+            Unit::Knot,
+            Unit::Beaufort,
+        ),
+        (Unit::Beaufort, Unit::KilometerPerHour) => convert(
+            convert(value, Unit::Beaufort, Unit::Knot).unwrap(),
+            Unit::Knot,
+            Unit::KilometerPerHour,
+        ),
+
+        (Unit::Milliwatt, Unit::DecibelMilliwatt) => Some(10.0 * value.log10()),
+        (Unit::DecibelMilliwatt, Unit::Milliwatt) => Some((10.0 as Float).powf(value / 10.0)),
+
+        (Unit::Angstrom, Unit::Nanometer) => Some(value / 10.0),
+        (Unit::Nanometer, Unit::Angstrom) => Some(value * 10.0),
+        (Unit::Nanometer, Unit::Micrometer) => Some(value / 1000.0),
+        (Unit::Micrometer, Unit::Nanometer) => Some(value * 1000.0),
+        (Unit::Kilometer, Unit::Au) => Some(value / 1.495_978_7e8),
+        (Unit::Au, Unit::Kilometer) => Some(value * 1.495_978_7e8),
+        (Unit::Au, Unit::LightYear) => Some(value / 63241.08),
+        (Unit::LightYear, Unit::Au) => Some(value * 63241.08),
+        (Unit::LightYear, Unit::Parsec) => Some(value / 3.26156),
+        (Unit::Parsec, Unit::LightYear) => Some(value * 3.26156),
+
+        _ => None,
+    }
+}
+
+/// Converts a difference between two values instead of a value, e.g. a rise of 10 C is a rise of 18 F (not 50 F).
+pub fn delta_convert(delta: Float, from: Unit, to: Unit) -> Option<Float> {
+    match (convert(0.0, from, to), convert(delta, from, to)) {
+        (Some(base), Some(point)) => Some(point - base),
+        _ => None,
+    }
+}
+
+/// Converts a small difference `delta` next to `value` (both in `from`) within `context`, unlike `delta_convert` it
+/// works for logarithmic and table-based units too, e.g. 1 mW more than 10 mW is 0.41 dBm more.
+fn delta_convert_at(delta: Float, value: Float, from: Unit, to: Unit, context: Context) -> Option<Float> {
+    Some(context.convert(value + delta, from, to)? - context.convert(value, from, to)?)
+}
+
+/// Converts `value` to `reference` within `context` for comparing it with other choices. Scales given by a table
+/// compare by their steps, e.g. 20 kn is neither weaker nor stronger than 5 Bft since force 5 spans 17 to 21 kn.
+fn equivalent_of(value: Float, unit: Unit, reference: Unit, context: Context) -> Option<Float> {
+    match reference.scale() {
+        Some(scale) => Some(scale.step_of(context.convert(value, unit, scale.base)?)),
+        None => context.convert(value, unit, reference),
+    }
+}
+
+/// Rounds `value` to a multiple of `step` with `rounding` (e.g. `Float::ceil`).
+fn round_to(value: Float, step: Float, rounding: fn(Float) -> Float) -> Float {
+    rounding(value / step) * step
+}
+
+impl Quantity {
+    /// Comparative used when asking which side is bigger (or smaller).
+    pub fn comparative(&self, polarity: Polarity) -> &'static str {
+        match (self, polarity) {
+            (Quantity::Temperature, Polarity::Greater) => "hotter",
+            (Quantity::Temperature, Polarity::Less) => "colder",
+            (Quantity::TemperatureDifference, Polarity::Greater) => "a bigger change",
+            (Quantity::TemperatureDifference, Polarity::Less) => "a smaller change",
+            (Quantity::Length, Polarity::Greater)
+            | (Quantity::ExtremeLength, Polarity::Greater)
+            | (Quantity::Time, Polarity::Greater) => "longer",
+            (Quantity::Length, Polarity::Less)
+            | (Quantity::ExtremeLength, Polarity::Less)
+            | (Quantity::Time, Polarity::Less) => "shorter",
+            (Quantity::Area, Polarity::Greater) => "larger",
+            (Quantity::Area, Polarity::Less) => "smaller",
+            (Quantity::Mass, Polarity::Greater) => "heavier",
+            (Quantity::Mass, Polarity::Less) => "lighter",
+            (Quantity::WindSpeed, Polarity::Greater) | (Quantity::Power, Polarity::Greater) => "stronger",
+            (Quantity::WindSpeed, Polarity::Less) | (Quantity::Power, Polarity::Less) => "weaker",
+            (Quantity::Pressure, Polarity::Greater) | (Quantity::Concentration, Polarity::Greater) => "higher",
+            (Quantity::Pressure, Polarity::Less) | (Quantity::Concentration, Polarity::Less) => "lower",
+            (Quantity::Volume, Polarity::Greater) | (Quantity::Energy, Polarity::Greater) => "more",
+            (Quantity::Volume, Polarity::Less) | (Quantity::Energy, Polarity::Less) => "less",
+        }
+    }
+
+    /// Superlative used when asking for the biggest (or smallest) of several choices.
+    pub fn superlative(&self, polarity: Polarity) -> &'static str {
+        match (self, polarity) {
+            (Quantity::Temperature, Polarity::Greater) => "hottest",
+            (Quantity::Temperature, Polarity::Less) => "coldest",
+            (Quantity::TemperatureDifference, Polarity::Greater) => "biggest change",
+            (Quantity::TemperatureDifference, Polarity::Less) => "smallest change",
+            (Quantity::Length, Polarity::Greater)
+            | (Quantity::ExtremeLength, Polarity::Greater)
+            | (Quantity::Time, Polarity::Greater) => "longest",
+            (Quantity::Length, Polarity::Less)
+            | (Quantity::ExtremeLength, Polarity::Less)
+            | (Quantity::Time, Polarity::Less) => "shortest",
+            (Quantity::Area, Polarity::Greater) => "largest",
+            (Quantity::Area, Polarity::Less) => "smallest",
+            (Quantity::Mass, Polarity::Greater) => "heaviest",
+            (Quantity::Mass, Polarity::Less) => "lightest",
+            (Quantity::WindSpeed, Polarity::Greater) | (Quantity::Power, Polarity::Greater) => "strongest",
+            (Quantity::WindSpeed, Polarity::Less) | (Quantity::Power, Polarity::Less) => "weakest",
+            (Quantity::Pressure, Polarity::Greater) | (Quantity::Concentration, Polarity::Greater) => "highest",
+            (Quantity::Pressure, Polarity::Less) | (Quantity::Concentration, Polarity::Less) => "lowest",
+            (Quantity::Volume, Polarity::Greater) | (Quantity::Energy, Polarity::Greater) => "most",
+            (Quantity::Volume, Polarity::Less) | (Quantity::Energy, Polarity::Less) => "least",
+        }
+    }
+
+    /// Scales whose zero is arbitrary, e.g. 0 C and 0 F are different temperatures. Gaps between their values can't be
+    /// relative to the values themselves.
+    fn has_arbitrary_zero(&self) -> bool {
+        matches!(self, Quantity::Temperature)
+    }
+
+    /// Quantities spanning orders of magnitude are sampled log-uniformly, the rest (including temperatures which can
+    /// go negative) uniformly. Powers are sampled in their linear unit which makes them uniform in decibels and
+    /// extreme lengths get even exponents.
+    pub fn sampling(&self) -> Sampling {
+        match self {
+            Quantity::Length
+            | Quantity::Area
+            | Quantity::Volume
+            | Quantity::Energy
+            | Quantity::Power
+            | Quantity::ExtremeLength => Sampling::LogUniform,
+            Quantity::Temperature
+            | Quantity::TemperatureDifference
+            | Quantity::Mass
+            | Quantity::Pressure
+            | Quantity::Time
+            | Quantity::WindSpeed
+            | Quantity::Concentration => Sampling::Uniform,
+        }
+    }
+
+    /// Pairs of the given category, ordered the same way as `Unit::pair_with` orders them.
+    pub fn unit_pairs_in(&self, category: Category) -> Vec<(Unit, Unit)> {
+        match category {
+            Category::Conversion => self.unit_pairs(),
+            Category::WithinSystem => self.within_system_pairs(),
+            Category::Substance => self.substance_pairs(),
+        }
+    }
+
+    /// Conversions between systems. Pairs are ordered the same way as `Unit::pair_with` orders them.
+    pub fn unit_pairs(&self) -> Vec<(Unit, Unit)> {
+        match self {
+            Quantity::Temperature => vec![Unit::Celsius.pair_with(Unit::Fahrenheit)],
+            Quantity::TemperatureDifference => vec![(Unit::FahrenheitDifference, Unit::CelsiusDifference)],
+            Quantity::Length => vec![
+                (Unit::Centimeter, Unit::Inch),
+                (Unit::Centimeter, Unit::Foot),
+                (Unit::Foot, Unit::Meter),
+                (Unit::Kilometer, Unit::Mile),
+                (Unit::Kilometer, Unit::NauticalMile),
+                (Unit::Mile, Unit::NauticalMile),
+            ],
+            Quantity::Area => vec![(Unit::SquareFoot, Unit::SquareMeter), (Unit::Acre, Unit::Hectare)],
+            Quantity::Volume => vec![
+                (Unit::Millilitre, Unit::FluidOunce),
+                (Unit::Millilitre, Unit::Cup),
+                (Unit::Litre, Unit::Gallon),
+            ],
+            Quantity::Mass => vec![
+                (Unit::Pound, Unit::Kilogram),
+                (Unit::Pound, Unit::Stone),
+                (Unit::Kilogram, Unit::Stone),
+            ],
+            Quantity::Energy => vec![(Unit::Joule, Unit::Calorie)],
+            Quantity::Pressure => vec![(Unit::Kilopascal, Unit::PoundPerSquareInch)],
+            Quantity::Time => vec![(Unit::Minute, Unit::Hour)],
+            Quantity::WindSpeed => vec![(Unit::KilometerPerHour, Unit::Beaufort), (Unit::Knot, Unit::Beaufort)],
+            Quantity::Power => vec![(Unit::DecibelMilliwatt, Unit::Milliwatt)],
+            // US and SI lab units, every context of theirs names the substance they convert by
+            Quantity::Concentration => vec![(Unit::MilligramPerDecilitre, Unit::MillimolePerLitre)],
+            Quantity::ExtremeLength => vec![
+                (Unit::Angstrom, Unit::Nanometer),
+                (Unit::Nanometer, Unit::Micrometer),
+                (Unit::Kilometer, Unit::Au),
+                (Unit::Au, Unit::LightYear),
+                (Unit::LightYear, Unit::Parsec),
+            ],
+        }
+    }
+
+    /// Units of the same system, empty for quantities without such units worth drilling.
+    fn within_system_pairs(&self) -> Vec<(Unit, Unit)> {
+        match self {
+            Quantity::Length => vec![(Unit::Centimeter, Unit::Meter), (Unit::Meter, Unit::Kilometer)],
+            Quantity::Area => vec![(Unit::SquareMeter, Unit::Hectare)],
+            Quantity::Volume => vec![(Unit::Millilitre, Unit::Litre), (Unit::FluidOunce, Unit::Cup)],
+            Quantity::Mass => vec![(Unit::Gram, Unit::Kilogram), (Unit::Ounce, Unit::Pound)],
+            Quantity::Temperature
+            | Quantity::TemperatureDifference
+            | Quantity::Energy
+            | Quantity::Pressure
+            | Quantity::Time
+            | Quantity::WindSpeed
+            | Quantity::Power
+            | Quantity::Concentration
+            | Quantity::ExtremeLength => vec![],
+        }
+    }
+
+    /// Volumes weighed for a given substance, empty for the rest.
+    fn substance_pairs(&self) -> Vec<(Unit, Unit)> {
+        match self {
+            Quantity::Volume => vec![(Unit::Millilitre, Unit::Gram), (Unit::Cup, Unit::Gram)],
+            Quantity::Temperature
+            | Quantity::TemperatureDifference
+            | Quantity::Length
+            | Quantity::Area
+            | Quantity::Mass
+            | Quantity::Energy
+            | Quantity::Pressure
+            | Quantity::Time
+            | Quantity::WindSpeed
+            | Quantity::Power
+            | Quantity::Concentration
+            | Quantity::ExtremeLength => vec![],
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Choice {
+    pub unit: Unit,
+    pub value: Float,
+    /// Value converted to the challenge's reference unit (the biggest unit among its choices) so that choices can be
+    /// compared directly.
+    pub equivalent: Float,
+    /// How the value is written when displayed, e.g. "5 ft 11 in" or "3/4 cup".
+    pub notation: Notation,
+    /// Calculation shown instead of the value, e.g. "2 × 5 km", the value is its result.
+    pub expression: Option<Expression>,
+}
+
+impl fmt::Display for Choice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.expression {
+            Some(expression) => write!(f, "{}", expression),
+            None => f.write_str(&self.notation.format(self.unit, self.value)),
+        }
+    }
+}
+
+/// Choice the player picked, two-sided cards have the left side first.
+#[derive(Clone, Copy, Debug)]
+pub enum ChoiceSelection {
+    Nth(usize),
+    Equal,
+}
+
+impl ChoiceSelection {
+    fn index(self) -> Option<usize> {
+        match self {
+            ChoiceSelection::Nth(idx) => Some(idx),
+            ChoiceSelection::Equal => None,
+        }
+    }
+}
+
+/// Whether the player is asked for the bigger or the smaller side.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Polarity {
+    Greater,
+    Less,
+}
+
+/// What the player is supposed to do with the choices.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChallengeKind {
+    /// Pick the biggest (or smallest) choice.
+    Pick,
+    /// Sort all the choices from the biggest to the smallest (or the other way round).
+    Order,
+    /// Type in the value of the only choice converted to `target`. Answers within `tolerance` relative error count.
+    Estimate { target: Unit, tolerance: Float },
+}
+
+#[derive(Clone, Debug)]
+pub struct Challenge {
+    /// Two choices for regular cards, more for multiple-choice and ordering ones and a single one for estimation.
+    /// Units are all different except for ordering where they can repeat.
+    pub choices: Vec<Choice>,
+    pub polarity: Polarity,
+    pub kind: ChallengeKind,
+    /// Difficulty the challenge was generated for, in rating points.
+    pub difficulty: Float,
+    /// Situation the values come from, shown as a hint.
+    pub context: Context,
+}
+
+#[derive(Clone, Debug)]
+pub enum Answer {
+    Pick(ChoiceSelection),
+    /// Indices of choices in the order given by the player.
+    Order(Vec<usize>),
+    Estimate(Float),
+}
+
+/// Result of answering a challenge. Ordering gives a point for every adjacent pair in the right order, everything
+/// else is all or nothing.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Grade {
+    pub points: usize,
+    pub max_points: usize,
+}
+
+impl Grade {
+    pub fn is_correct(&self) -> bool {
+        self.points == self.max_points
+    }
+}
+
+/// Knobs controlling how challenges are generated.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GenerationSettings {
+    /// Minimal gap between the two sides relative to the bigger of them, e.g. `0.05` means the sides have to differ
+    /// by at least 5%. Temperatures are measured against the context's range instead. Has to be in `[0, 1)`.
+    pub min_relative_gap: Float,
+    /// Probability of deliberately generating a challenge with both sides equal.
+    pub equal_probability: Float,
+    /// Probability of asking for the smaller side instead of the bigger one.
+    pub less_probability: Float,
+    /// Probability of generating a multiple-choice challenge (if the game has enough related units for it).
+    pub multiple_choice_probability: Float,
+    /// Number of choices in multiple-choice challenges.
+    pub multiple_choice_count: usize,
+    /// Probability of generating an ordering challenge.
+    pub ordering_probability: Float,
+    /// Probability of generating an estimation challenge.
+    pub estimation_probability: Float,
+    /// Relative error accepted in estimation challenges at level 0, it gets tighter with every level.
+    pub estimation_tolerance: Float,
+    /// Chance of success the challenges are aimed at, judging by the player's rating.
+    pub target_success_probability: Float,
+    /// Probability of showing one side of a two-sided challenge as an expression, e.g. "2 × 5 km".
+    pub expression_probability: Float,
+    /// Lowest level (fractional) at which expressions show up, they're meant for advanced players.
+    pub expression_min_level: Float,
+}
+
+impl Default for GenerationSettings {
+    fn default() -> Self {
+        GenerationSettings {
+            min_relative_gap: 0.02,
+            equal_probability: 0.1,
+            less_probability: 0.3,
+            multiple_choice_probability: 0.2,
+            multiple_choice_count: 3,
+            ordering_probability: 0.1,
+            estimation_probability: 0.1,
+            estimation_tolerance: 0.25,
+            target_success_probability: 0.75,
+            expression_probability: 0.2,
+            expression_min_level: 10.0,
+        }
+    }
+}
+
+/// Reason why a challenge was rejected by `Challenge::validate`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InvalidChallenge {
+    WrongChoiceCount,
+    SameUnit,
+    Unconvertible,
+    NotFinite,
+    GapTooSmall { relative_gap: Float },
+}
+
+fn relative_gap(a: Float, b: Float, magnitude: Float) -> Float {
+    if magnitude > 0.0 {
+        (a - b).abs() / magnitude
+    } else {
+        0.0
+    }
+}
+
+impl Challenge {
+    /// Creates a challenge with choices in the given order, set in the first context that fits its units (or at least
+    /// its reference unit).
+    pub fn new(polarity: Polarity, measurements: &[(Unit, Float)]) -> Self {
+        let units: Vec<Unit> = measurements.iter().map(|(unit, _)| *unit).collect();
+        let reference = *units.iter().max().expect("no measurements");
+        let context = Context::all_for(&units)
+            .first()
+            .copied()
+            .unwrap_or_else(|| Context::all_for(&[reference])[0]);
+        let choices = measurements
+            .iter()
+            .map(|(unit, value)| Choice {
+                unit: *unit,
+                value: *value,
+                equivalent: equivalent_of(*value, *unit, reference, context).unwrap(),
+                notation: Notation::Decimal,
+                expression: None,
+            })
+            .collect();
+        Self {
+            choices,
+            polarity,
+            kind: ChallengeKind::Pick,
+            difficulty: rating::BASE_DIFFICULTY,
+            context,
+        }
+    }
+
+    /// Moves the challenge to `context`, its choices get written in the context's notation and compared by the
+    /// context's substance (if any).
+    fn in_context(mut self, context: Context) -> Self {
+        let reference = self.reference();
+        for choice in self.choices.iter_mut() {
+            choice.notation = context.notation(choice.unit);
+            choice.equivalent =
+                equivalent_of(choice.value, choice.unit, reference, context).expect("choice outside of context");
+        }
+        Self { context, ..self }
+    }
+
+    /// The biggest unit among the choices, all of them are compared in it.
+    fn reference(&self) -> Unit {
+        self.choices.iter().map(|choice| choice.unit).max().unwrap()
+    }
+
+    /// Checks that the challenge has a single clear answer.
+    ///
+    /// For picking the choices have to use different units and the best one either beats the runner-up by at least
+    /// `settings.min_relative_gap` or they're a tie (see `is_tie`). For ordering there has to be
+    /// at least `settings.min_relative_gap` between all neighbours and at least two different units. For estimation
+    /// the only choice has to be convertible to the target unit.
+    pub fn validate(&self, settings: &GenerationSettings) -> Result<(), InvalidChallenge> {
+        let enough_choices = match self.kind {
+            ChallengeKind::Pick | ChallengeKind::Order => self.choices.len() >= 2,
+            ChallengeKind::Estimate { .. } => self.choices.len() == 1,
+        };
+        if !enough_choices {
+            return Err(InvalidChallenge::WrongChoiceCount);
+        }
+        if self
+            .choices
+            .iter()
+            .any(|choice| !choice.value.is_finite() || !choice.equivalent.is_finite())
+        {
+            return Err(InvalidChallenge::NotFinite);
+        }
+        match self.kind {
+            ChallengeKind::Pick => {
+                for (idx, choice) in self.choices.iter().enumerate() {
+                    if self.choices[idx + 1..].iter().any(|other| other.unit == choice.unit) {
+                        return Err(InvalidChallenge::SameUnit);
+                    }
+                }
+                let relative_gap = self.relative_gap();
+                if !self.is_tie() && relative_gap < settings.min_relative_gap {
+                    return Err(InvalidChallenge::GapTooSmall { relative_gap });
+                }
+            }
+            ChallengeKind::Order => {
+                if self.choices.iter().all(|choice| choice.unit == self.choices[0].unit) {
+                    return Err(InvalidChallenge::SameUnit);
+                }
+                let ranking = self.ranking();
+                for neighbours in ranking.windows(2) {
+                    let relative_gap = self.relative_gap_between(
+                        self.choices[neighbours[0]].equivalent,
+                        self.choices[neighbours[1]].equivalent,
+                    );
+                    if relative_gap < settings.min_relative_gap {
+                        return Err(InvalidChallenge::GapTooSmall { relative_gap });
+                    }
+                }
+            }
+            ChallengeKind::Estimate { target, .. } => {
+                if self.choices[0].unit == target {
+                    return Err(InvalidChallenge::SameUnit);
+                }
+                match self.expected_estimate() {
+                    Some(expected) if !expected.is_finite() => return Err(InvalidChallenge::NotFinite),
+                    Some(_) => {}
+                    None => return Err(InvalidChallenge::Unconvertible),
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Exact answer to an estimation challenge.
+    pub fn expected_estimate(&self) -> Option<Float> {
+        match self.kind {
+            ChallengeKind::Estimate { target, .. } => {
+                self.context
+                    .convert(self.choices[0].value, self.choices[0].unit, target)
+            }
+            _ => None,
+        }
+    }
+
+    /// Relative error of `value` as an answer to an estimation challenge. Logarithmic targets are compared by what
+    /// they stand for in the shown unit since a single dB is already a quarter more power. Scales with an arbitrary
+    /// zero go by the context's range, like gaps do. Table scales go by steps, the right one is no error and every
+    /// step off is a whole one.
+    fn estimate_error(&self, value: Float) -> Option<Float> {
+        let expected = self.expected_estimate()?;
+        let target = match self.kind {
+            ChallengeKind::Estimate { target, .. } => target,
+            _ => return None,
+        };
+        if let Some(scale) = target.scale() {
+            let step = |position| scale.step_of(scale.base_value_at(position));
+            Some((step(value) - step(expected)).abs())
+        } else if target.is_logarithmic() {
+            let shown = &self.choices[0];
+            Some((self.context.convert(value, target, shown.unit)? - shown.value).abs() / shown.value.abs())
+        } else if target.quantity().has_arbitrary_zero() {
+            let (min, max) = self.context.range_in(target);
+            Some((value - expected).abs() / (max - min))
+        } else {
+            // small values would make the error explode so anything below a single unit counts as one
+            Some((value - expected).abs() / expected.abs().max(1.0))
+        }
+    }
+
+    /// Indices of choices from the best (biggest or smallest depending on polarity) to the worst.
+    fn ranking(&self) -> Vec<usize> {
+        let mut ranking: Vec<usize> = (0..self.choices.len()).collect();
+        ranking.sort_by(|a, b| {
+            let ordering = self.choices[*a]
+                .equivalent
+                .partial_cmp(&self.choices[*b].equivalent)
+                .expect("equivalent is NaN");
+            match self.polarity {
+                Polarity::Greater => ordering.reverse(),
+                Polarity::Less => ordering,
+            }
+        });
+        ranking
+    }
+
+    /// Gap between the best choice and the runner-up.
+    fn relative_gap(&self) -> Float {
+        let ranking = self.ranking();
+        self.relative_gap_between(self.choices[ranking[0]].equivalent, self.choices[ranking[1]].equivalent)
+    }
+
+    /// Gap between two equivalents relative to the bigger of them. Scales with an arbitrary zero go by the context's
+    /// range instead, otherwise 0.1 C and 0.2 C would be as far apart as 10 C and 20 C.
+    fn relative_gap_between(&self, a: Float, b: Float) -> Float {
+        let reference = self.reference();
+        let magnitude = if reference.quantity().has_arbitrary_zero() {
+            let (min, max) = self.context.range_in(reference);
+            max - min
+        } else {
+            a.abs().max(b.abs())
+        };
+        relative_gap(a, b, magnitude)
+    }
+
+    /// Question shown to the player, e.g. "Which is colder?".
+    pub fn prompt(&self) -> String {
+        let quantity = self.reference().quantity();
+        let opposite = match self.polarity {
+            Polarity::Greater => Polarity::Less,
+            Polarity::Less => Polarity::Greater,
+        };
+        match self.kind {
+            ChallengeKind::Pick if self.choices.len() > 2 => {
+                format!("Which is the {}?", quantity.superlative(self.polarity))
+            }
+            ChallengeKind::Pick => format!("Which is {}?", quantity.comparative(self.polarity)),
+            ChallengeKind::Order => format!(
+                "Order from {} to {}",
+                quantity.superlative(self.polarity),
+                quantity.superlative(opposite)
+            ),
+            ChallengeKind::Estimate { target, .. } => format!("How much is that in {}?", target),
+        }
+    }
+
+    pub fn grade(&self, answer: &Answer) -> Grade {
+        match (self.kind, answer) {
+            (ChallengeKind::Pick, Answer::Pick(selection)) => Grade {
+                points: self.is_correct(*selection) as usize,
+                max_points: 1,
+            },
+            (ChallengeKind::Order, Answer::Order(order)) => Grade {
+                points: self.correctly_ordered_pairs(order),
+                max_points: self.choices.len() - 1,
+            },
+            (ChallengeKind::Estimate { tolerance, .. }, Answer::Estimate(value)) => Grade {
+                points: self.estimate_error(*value).is_some_and(|error| error <= tolerance) as usize,
+                max_points: 1,
+            },
+            _ => Grade {
+                points: 0,
+                max_points: 1,
+            },
+        }
+    }
+
+    /// Correct answer spelled out for the player, e.g. "30 C is hotter".
+    pub fn solution(&self) -> String {
+        let quantity = self.reference().quantity();
+        let describe = |idx: usize| self.choices[idx].to_string();
+        let ranking = self.ranking();
+        match self.kind {
+            ChallengeKind::Pick if self.is_tie() => {
+                format!("{} = {}", describe(ranking[0]), describe(ranking[1]))
+            }
+            ChallengeKind::Pick if self.choices.len() > 2 => {
+                format!(
+                    "{} is the {}",
+                    describe(ranking[0]),
+                    quantity.superlative(self.polarity)
+                )
+            }
+            ChallengeKind::Pick => format!("{} is {}", describe(ranking[0]), quantity.comparative(self.polarity)),
+            ChallengeKind::Order => {
+                let separator = match self.polarity {
+                    Polarity::Greater => " > ",
+                    Polarity::Less => " < ",
+                };
+                ranking.into_iter().map(describe).collect::<Vec<_>>().join(separator)
+            }
+            ChallengeKind::Estimate { target, .. } => {
+                let expected = self.expected_estimate().unwrap_or(Float::NAN);
+                format!(
+                    "{} = {}",
+                    describe(0),
+                    self.context.notation(target).format(target, expected)
+                )
+            }
+        }
+    }
+
+    /// Whether the best choice and the runner-up are written the same once converted, i.e. each of them converted to
+    /// the other's unit reads exactly like the other one. Ties only go as far as the player can see, 47 C and 117 F
+    /// differ by a few tenths of a degree which both units show. Table scales tie whenever both fall into one step.
+    fn is_tie(&self) -> bool {
+        let ranking = self.ranking();
+        let (a, b) = (self.choices[ranking[0]], self.choices[ranking[1]]);
+        if self.reference().scale().is_some() {
+            return a.equivalent == b.equivalent;
+        }
+        let reads_as = |from: Choice, to: Choice| match self.context.convert(from.value, from.unit, to.unit) {
+            Some(value) => to.notation.format(to.unit, value) == to.notation.format(to.unit, to.value),
+            None => false,
+        };
+        reads_as(a, b) && reads_as(b, a)
+    }
+
+    fn is_correct(&self, selection: ChoiceSelection) -> bool {
+        let equal = self.is_tie();
+        match selection.index() {
+            Some(idx) => !equal && self.ranking()[0] == idx,
+            None => equal,
+        }
+    }
+
+    /// Number of adjacent pairs in `order` that are in the right order. Anything but a permutation of choices gets
+    /// nothing.
+    fn correctly_ordered_pairs(&self, order: &[usize]) -> usize {
+        let mut sorted = order.to_vec();
+        sorted.sort_unstable();
+        if sorted != (0..self.choices.len()).collect::<Vec<_>>() {
+            return 0;
+        }
+        order
+            .windows(2)
+            .filter(|pair| {
+                let (first, second) = (self.choices[pair[0]].equivalent, self.choices[pair[1]].equivalent);
+                match self.polarity {
+                    Polarity::Greater => first > second,
+                    Polarity::Less => first < second,
+                }
+            })
+            .count()
+    }
+
+    /// All pairs of different units present in the challenge (including the estimation target).
+    fn unit_pairs(&self) -> Vec<(Unit, Unit)> {
+        let mut unit_pairs = Vec::new();
+        if let ChallengeKind::Estimate { target, .. } = self.kind {
+            unit_pairs.push(self.choices[0].unit.pair_with(target));
+        }
+        for (idx, choice) in self.choices.iter().enumerate() {
+            for other in &self.choices[idx + 1..] {
+                if choice.unit != other.unit {
+                    unit_pairs.push(choice.unit.pair_with(other.unit));
+                }
+            }
+        }
+        unit_pairs.sort();
+        unit_pairs.dedup();
+        unit_pairs
+    }
+
+    fn random_polarity(settings: &GenerationSettings) -> Polarity {
+        if random::<Float>() < settings.less_probability {
+            Polarity::Less
+        } else {
+            Polarity::Greater
+        }
+    }
+
+    fn generate(
+        units: &[Unit],
+        context: Context,
+        difficulty: Float,
+        curve: &dyn DifficultyCurve,
+        settings: &GenerationSettings,
+    ) -> Self {
+        assert!(units.len() >= 2);
+        assert!((0.0..1.0).contains(&settings.min_relative_gap));
+        let polarity = Self::random_polarity(settings);
+        let tie = if units.len() == 2 && random::<Float>() < settings.equal_probability {
+            Self::generate_tie(units[0], units[1], context, polarity)
+        } else {
+            None
+        };
+        let challenge = Self {
+            difficulty,
+            ..tie.unwrap_or_else(|| Self::generate_distinct(units, context, difficulty, curve, polarity, settings))
+        }
+        .in_context(context);
+        let challenge = if units.len() == 2
+            && rating::difficulty_level(difficulty) >= settings.expression_min_level
+            && random::<Float>() < settings.expression_probability
+        {
+            challenge.with_expression(settings)
+        } else {
+            challenge
+        };
+        debug_assert_eq!(challenge.validate(settings), Ok(()), "{:?}", challenge);
+        challenge
+    }
+
+    /// Looks for round values that read the same in both units. Gives up after a few attempts since
+    /// some unit pairs have only a handful of such values.
+    fn generate_tie(a: Unit, b: Unit, context: Context, polarity: Polarity) -> Option<Self> {
+        const ATTEMPTS: usize = 100;
+        let (bigger, smaller) = if a > b { (a, b) } else { (b, a) };
+        let (min_value, max_value) = context.range_in(bigger);
+        let (min_value, max_value) = (min_value.ceil(), max_value.floor());
+        (0..ATTEMPTS)
+            .map(|_| {
+                round_to(
+                    context.sampling().sample(min_value, max_value),
+                    context.step(bigger, 1.0),
+                    Float::round,
+                )
+            })
+            .map(|bigger_value| {
+                let smaller_value = round_to(
+                    context.convert(bigger_value, bigger, smaller).unwrap(),
+                    context.step(smaller, 1.0),
+                    Float::round,
+                );
+                Self::shuffled(polarity, vec![(bigger, bigger_value), (smaller, smaller_value)]).in_context(context)
+            })
+            .find(Self::is_tie)
+    }
+
+    /// Places the winner `delta` above the mid point and the runner-up `delta` below it (mirrored for `Less`
+    /// polarity), both measured in the context's sampling space so that log-uniform deltas are relative. The rest of
+    /// the choices (if any) land somewhere further away. Values get decimal places once the gap is too small for
+    /// whole units.
+    fn generate_distinct(
+        units: &[Unit],
+        context: Context,
+        difficulty: Float,
+        curve: &dyn DifficultyCurve,
+        polarity: Polarity,
+        settings: &GenerationSettings,
+    ) -> Self {
+        let reference = *units.iter().max().unwrap();
+        let sampling = context.sampling();
+        let (low, high) = context.space_range_in(reference);
+        // a curve's floor can be too coarse for narrow contexts so keep it below half of the range
+        let delta = curve
+            .delta(reference, context, rating::difficulty_level(difficulty))
+            .min((high - low) / 2.1);
+        let mid_point = {
+            let min_allowed = low + delta;
+            let max_allowed = high - delta;
+            assert!(min_allowed < max_allowed);
+            min_allowed + (max_allowed - min_allowed) * random::<Float>()
+        };
+        // aiming a bit higher than the min gap so that float errors don't break it when nothing gets rounded
+        let min_gap = settings.min_relative_gap * 1.001;
+        let delta = delta.max(context.min_delta(reference, mid_point, min_gap));
+        // the rest of the losers spread from the runner-up towards the end of the range (if there's any room left)
+        let (winner_value, loser_value, loser_room) = match polarity {
+            Polarity::Greater => (
+                mid_point + delta,
+                mid_point - delta,
+                -(mid_point - delta - low).max(0.0),
+            ),
+            Polarity::Less => (
+                mid_point - delta,
+                mid_point + delta,
+                (high - mid_point - delta).max(0.0),
+            ),
+        };
+        // round away from the mid point
+        let half_gap = (sampling.value_at(mid_point + delta) - sampling.value_at(mid_point - delta)) / 2.0;
+        let round = |value: Float, unit: Unit, up: bool| {
+            let resolution = sampling.resolution(value, half_gap);
+            let step = context.step(
+                unit,
+                delta_convert_at(resolution, value, reference, unit, context)
+                    .unwrap()
+                    .abs(),
+            );
+            round_to(
+                context.convert(value, reference, unit).unwrap(),
+                step,
+                if up { Float::ceil } else { Float::floor },
+            )
+        };
+        let winner_up = polarity == Polarity::Greater;
+
+        let mut units = units.to_vec();
+        units.shuffle(&mut rand::thread_rng());
+        let measurements = units
+            .iter()
+            .enumerate()
+            .map(|(idx, unit)| {
+                let (value, up) = match idx {
+                    0 => (winner_value, winner_up),
+                    1 => (loser_value, !winner_up),
+                    _ => (loser_value + loser_room * random::<Float>(), !winner_up),
+                };
+                (*unit, round(sampling.value_at(value), *unit, up))
+            })
+            .collect();
+        Self::shuffled(polarity, measurements)
+    }
+
+    /// Generates an ordering challenge with a choice for every unit in `units` (they can repeat). Neighbours are
+    /// evenly spaced (in the context's sampling space) with the difficulty's delta split between them so that the
+    /// whole spread fits in the range. When the gaps don't hold even with the choices spread over the whole range
+    /// (too many choices for a narrow context) it falls back to picking between two of the units.
+    fn generate_order(
+        units: &[Unit],
+        context: Context,
+        difficulty: Float,
+        curve: &dyn DifficultyCurve,
+        settings: &GenerationSettings,
+    ) -> Self {
+        const ATTEMPTS: usize = 100;
+        assert!(units.len() >= 2);
+        let polarity = Self::random_polarity(settings);
+        let reference = *units.iter().max().unwrap();
+        let sampling = context.sampling();
+        let (low, high) = context.space_range_in(reference);
+        let widest = (high - low) / (units.len() - 1) as Float;
+        let mut spacing = (2.0 * curve.delta(reference, context, rating::difficulty_level(difficulty))
+            / (units.len() - 1) as Float)
+            .min(widest);
+        for _ in 0..ATTEMPTS {
+            let span = spacing * (units.len() - 1) as Float;
+            let start = low + (high - low - span).max(0.0) * random::<Float>();
+            // the first two are the closest ones, rounding to half of their gap keeps all neighbours apart
+            let half_gap = (sampling.value_at(start + spacing) - sampling.value_at(start)) / 2.0;
+            let mut units = units.to_vec();
+            units.shuffle(&mut rand::thread_rng());
+            let measurements = units
+                .iter()
+                .enumerate()
+                .map(|(idx, unit)| {
+                    let value = sampling.value_at(start + spacing * idx as Float);
+                    let resolution = sampling.resolution(value, half_gap);
+                    let step = context.step(
+                        *unit,
+                        delta_convert_at(resolution, value, reference, *unit, context)
+                            .unwrap()
+                            .abs(),
+                    );
+                    (
+                        *unit,
+                        round_to(context.convert(value, reference, *unit).unwrap(), step, Float::round),
+                    )
+                })
+                .collect();
+            let challenge = Self {
+                kind: ChallengeKind::Order,
+                difficulty,
+                ..Self::shuffled(polarity, measurements)
+            }
+            .in_context(context);
+            // relative gaps depend on where the values land so just widen the spacing until they fit
+            if challenge.validate(settings).is_ok() {
+                return challenge;
+            }
+            if spacing >= widest {
+                break;
+            }
+            spacing = (spacing * 1.5).min(widest);
+        }
+        let other = *units
+            .iter()
+            .find(|unit| **unit != units[0])
+            .expect("ordering of a single unit");
+        Self::generate(&[units[0], other], context, difficulty, curve, settings)
+    }
+
+    /// Generates an estimation challenge showing a round value in one of the units and asking for it in the other
+    /// one. The value is sampled in the bigger unit (logarithmic ones can't be sampled log-uniformly) and rounded to
+    /// a tenth of the context's range or of its own magnitude for log-uniform contexts. The accepted error shrinks
+    /// with every level down to 1%.
+    fn generate_estimate(
+        unit_pair: (Unit, Unit),
+        context: Context,
+        difficulty: Float,
+        settings: &GenerationSettings,
+    ) -> Self {
+        let (shown, target) = if random::<bool>() {
+            unit_pair
+        } else {
+            (unit_pair.1, unit_pair.0)
+        };
+        let reference = shown.max(target);
+        let (min_value, max_value) = context.range_in(reference);
+        let sampling = context.sampling();
+        let value = sampling.sample(min_value, max_value);
+        let resolution = sampling.resolution(value, max_value - min_value) / 10.0;
+        let step = context.step(
+            shown,
+            delta_convert_at(resolution, value, reference, shown, context)
+                .unwrap()
+                .abs(),
+        );
+        let value = round_to(context.convert(value, reference, shown).unwrap(), step, Float::round);
+        let level = rating::difficulty_level(difficulty);
+        let tolerance = (settings.estimation_tolerance * (0.85 as Float).powf(level)).max(0.01);
+        let challenge = Self {
+            kind: ChallengeKind::Estimate { target, tolerance },
+            difficulty,
+            ..Self::new(Polarity::Greater, &[(shown, value)])
+        }
+        .in_context(context);
+        debug_assert_eq!(challenge.validate(settings), Ok(()), "{:?}", challenge);
+        challenge
+    }
+
+    /// Shows a random choice as an expression of about the same value, e.g. "3 ft + 20 in". The challenge stays as it
+    /// is when there's no expression for the choice, it's written in scientific notation or the changed value would
+    /// blur the answer.
+    fn with_expression(self, settings: &GenerationSettings) -> Self {
+        let idx = rand::thread_rng().gen_range(0..self.choices.len());
+        let choice = self.choices[idx];
+        let expression = match Expression::near(choice.unit, choice.value) {
+            Some(expression) if !matches!(choice.notation, Notation::Scientific { .. }) => expression,
+            _ => return self,
+        };
+        let reference = self.reference();
+        let value = expression
+            .evaluate(choice.unit)
+            .expect("expression of another quantity");
+        let mut challenge = self.clone();
+        challenge.choices[idx] = Choice {
+            value,
+            equivalent: equivalent_of(value, choice.unit, reference, self.context).unwrap(),
+            expression: Some(expression),
+            ..choice
+        };
+        match challenge.validate(settings) {
+            Ok(()) => challenge,
+            Err(_) => self,
+        }
+    }
+
+    fn shuffled(polarity: Polarity, mut measurements: Vec<(Unit, Float)>) -> Self {
+        measurements.shuffle(&mut rand::thread_rng());
+        Self::new(polarity, &measurements)
+    }
+}
+
+const ORDERING_MIN_COUNT: usize = 3;
+const ORDERING_MAX_COUNT: usize = 5;
+
+pub const BLITZ_TIME_LIMIT: Duration = Duration::from_secs(60);
+pub const DEFAULT_LIVES: usize = 3;
+pub const DEFAULT_ROUND_CARDS: usize = 20;
+pub const DEFAULT_ROUND_LEVEL: Level = 5;
+pub const DEFAULT_PRACTICE_RETRY_AFTER: usize = 2;
+
+/// Source of time for the game, injected so that timed games can be tested without waiting.
+pub trait Clock: fmt::Debug {
+    /// Time elapsed since an arbitrary but fixed point in the past.
+    fn now(&self) -> Duration;
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum GameMode {
+    /// The game ends on the first mistake. A partly right order is a mistake too, its points still count towards the
+    /// accuracy.
+    SuddenDeath,
+    /// As many cards as possible before the time runs out, mistakes don't end the game.
+    Blitz { time_limit: Duration },
+    /// Every mistake (partly right orders included) costs a life, the game ends when there are none left.
+    Lives { lives: usize },
+    /// Fixed number of cards at a fixed level, every card is answered and the game ends with a letter grade. The
+    /// difficulty doesn't adapt and ratings don't change so that grades of the same round are comparable.
+    Round { cards: usize, level: Level },
+    /// Endless game where a similar challenge comes back after every mistake once `retry_after` other cards went by.
+    Practice { retry_after: usize },
+}
+
+impl GameMode {
+    pub fn blitz() -> Self {
+        GameMode::Blitz {
+            time_limit: BLITZ_TIME_LIMIT,
+        }
+    }
+
+    pub fn lives() -> Self {
+        GameMode::Lives { lives: DEFAULT_LIVES }
+    }
+
+    pub fn round() -> Self {
+        GameMode::Round {
+            cards: DEFAULT_ROUND_CARDS,
+            level: DEFAULT_ROUND_LEVEL,
+        }
+    }
+
+    pub fn practice() -> Self {
+        GameMode::Practice {
+            retry_after: DEFAULT_PRACTICE_RETRY_AFTER,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LetterGrade {
+    A,
+    B,
+    C,
+    D,
+    F,
+}
+
+impl LetterGrade {
+    fn from_accuracy(accuracy: Float) -> Self {
+        if accuracy >= 0.9 {
+            LetterGrade::A
+        } else if accuracy >= 0.8 {
+            LetterGrade::B
+        } else if accuracy >= 0.7 {
+            LetterGrade::C
+        } else if accuracy >= 0.6 {
+            LetterGrade::D
+        } else {
+            LetterGrade::F
+        }
+    }
+}
+
+impl fmt::Display for LetterGrade {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self, f)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameSummary {
+    pub mode: GameMode,
+    /// Cards answered before the game ended, answers given after the time limit are not counted.
+    pub answered: usize,
+    pub correct: usize,
+    /// Points from all the answered cards, ordering cards give partial credit.
+    pub points: usize,
+    pub max_points: usize,
+    /// Time played, capped at the time limit for timed games.
+    pub duration: Duration,
+    /// Final rating of every unit pair.
+    pub ratings: Vec<((Unit, Unit), Rating)>,
+    /// Solution of the last card if it was answered wrong, the player hasn't seen it when the mistake ended the game.
+    pub correction: Option<String>,
+}
+
+impl GameSummary {
+    /// Share of the points scored, between 0 and 1.
+    pub fn accuracy(&self) -> Float {
+        if self.max_points == 0 {
+            0.0
+        } else {
+            self.points as Float / self.max_points as Float
+        }
+    }
+
+    /// Final grade, only rounds are graded.
+    pub fn letter_grade(&self) -> Option<LetterGrade> {
+        match self.mode {
+            GameMode::Round { .. } => Some(LetterGrade::from_accuracy(self.accuracy())),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Game {
+    pub in_progress: bool,
+    mode: GameMode,
+    clock: Box<dyn Clock>,
+    started_at: Duration,
+    ended_at: Option<Duration>,
+    answered: usize,
+    correct: usize,
+    points: usize,
+    max_points: usize,
+    /// Solution of the last challenge if it was answered wrong.
+    correction: Option<String>,
+    /// Missed challenges to be retried once `answered` reaches the given count, practice only.
+    retries: VecDeque<(usize, Challenge)>,
+    rating_per_unit_pair: HashMap<(Unit, Unit), Rating>,
+    scheduler: Box<dyn PairScheduler>,
+    curve: Box<dyn DifficultyCurve>,
+    settings: GenerationSettings,
+    pub challenge: Challenge,
+}
+
+impl Game {
+    /// Rounds pick unit pairs uniformly so that they stay comparable, other games focus on the player's weak pairs.
+    pub fn new_with_single_quantity(
+        quantity: Quantity,
+        categories: &[Category],
+        mode: GameMode,
+        clock: Box<dyn Clock>,
+    ) -> Self {
+        let scheduler: Box<dyn PairScheduler> = match mode {
+            GameMode::Round { .. } => Box::new(UniformScheduler),
+            _ => Box::new(WeightedScheduler::default()),
+        };
+        Self::new_with_settings(
+            quantity,
+            categories,
+            mode,
+            GenerationSettings::default(),
+            scheduler,
+            Self::default_curve(),
+            clock,
+        )
+    }
+
+    /// Default curve everywhere except for temperatures, their range is narrow so they shrink slower to keep decimals
+    /// out of the first few dozen levels.
+    fn default_curve() -> Box<dyn DifficultyCurve> {
+        Box::new(QuantityCurves::new(Box::new(GeometricCurve::default())).with_override(
+            Quantity::Temperature,
+            Box::new(GeometricCurve {
+                ratio: 0.93,
+                ..GeometricCurve::default()
+            }),
+        ))
+    }
+
+    pub fn new_with_settings(
+        quantity: Quantity,
+        categories: &[Category],
+        mode: GameMode,
+        settings: GenerationSettings,
+        mut scheduler: Box<dyn PairScheduler>,
+        curve: Box<dyn DifficultyCurve>,
+        clock: Box<dyn Clock>,
+    ) -> Self {
+        let mut rating_per_unit_pair = HashMap::new();
+        for category in categories {
+            for pair in quantity.unit_pairs_in(*category) {
+                rating_per_unit_pair.insert(pair, Rating::default());
+            }
+        }
+        assert!(!rating_per_unit_pair.is_empty(), "no unit pairs to play");
+        let unit_pair = scheduler.choose(&rating_per_unit_pair, &mut rand::thread_rng());
+        let challenge = Self::next_challenge(unit_pair, &rating_per_unit_pair, mode, curve.as_ref(), &settings);
+        let started_at = clock.now();
+        Self {
+            in_progress: true,
+            mode,
+            clock,
+            started_at,
+            ended_at: None,
+            answered: 0,
+            correct: 0,
+            points: 0,
+            max_points: 0,
+            correction: None,
+            retries: VecDeque::new(),
+            rating_per_unit_pair,
+            scheduler,
+            curve,
+            settings,
+            challenge,
+        }
+    }
+
+    /// Builds a challenge around `unit_pair`, extending it to a multiple-choice or an ordering challenge or turning
+    /// it into an estimation one now and then. Extra units have to be paired with all the already chosen ones so that
+    /// their ratings are known.
+    fn next_challenge(
+        unit_pair: (Unit, Unit),
+        rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>,
+        mode: GameMode,
+        curve: &dyn DifficultyCurve,
+        settings: &GenerationSettings,
+    ) -> Challenge {
+        let mut rng = rand::thread_rng();
+        let mut related = vec![unit_pair.0, unit_pair.1];
+        let mut candidates: Vec<Unit> = rating_per_unit_pair.keys().flat_map(|(a, b)| vec![*a, *b]).collect();
+        candidates.sort();
+        candidates.dedup();
+        candidates.shuffle(&mut rng);
+        for candidate in candidates {
+            if related
+                .iter()
+                .all(|unit| rating_per_unit_pair.contains_key(&unit.pair_with(candidate)))
+            {
+                related.push(candidate);
+            }
+        }
+
+        let roll = random::<Float>();
+        if roll < settings.estimation_probability {
+            let units = &related[..2];
+            return Challenge::generate_estimate(
+                (units[0], units[1]),
+                Self::random_context(units),
+                Self::difficulty_of(rating_per_unit_pair, mode, units, settings),
+                settings,
+            );
+        }
+        let roll = roll - settings.estimation_probability;
+        if roll < settings.ordering_probability {
+            let count = rng.gen_range(ORDERING_MIN_COUNT..=ORDERING_MAX_COUNT);
+            let mut units = related[..2].to_vec();
+            while units.len() < count {
+                units.push(*related.choose(&mut rng).unwrap());
+            }
+            Challenge::generate_order(
+                &units,
+                Self::random_context(&units),
+                Self::difficulty_of(rating_per_unit_pair, mode, &units, settings),
+                curve,
+                settings,
+            )
+        } else if roll < settings.ordering_probability + settings.multiple_choice_probability
+            && related.len() >= settings.multiple_choice_count
+        {
+            let units = &related[..settings.multiple_choice_count];
+            Challenge::generate(
+                units,
+                Self::random_context(units),
+                Self::difficulty_of(rating_per_unit_pair, mode, units, settings),
+                curve,
+                settings,
+            )
+        } else {
+            let units = &related[..2];
+            Challenge::generate(
+                units,
+                Self::random_context(units),
+                Self::difficulty_of(rating_per_unit_pair, mode, units, settings),
+                curve,
+                settings,
+            )
+        }
+    }
+
+    fn random_context(units: &[Unit]) -> Context {
+        *Context::all_for(units)
+            .choose(&mut rand::thread_rng())
+            .expect("no context for units")
+    }
+
+    /// Difficulty aimed at the weakest pair among `units`, rounds stick to their level instead.
+    fn difficulty_of(
+        rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>,
+        mode: GameMode,
+        units: &[Unit],
+        settings: &GenerationSettings,
+    ) -> Float {
+        if let GameMode::Round { level, .. } = mode {
+            return rating::level_difficulty(level);
+        }
+        units
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, unit)| units[idx + 1..].iter().map(move |other| unit.pair_with(*other)))
+            .filter_map(|pair| rating_per_unit_pair.get(&pair))
+            .min_by(|a, b| a.value.partial_cmp(&b.value).expect("rating is NaN"))
+            .copied()
+            .unwrap_or_default()
+            .difficulty_for(settings.target_success_probability)
+    }
+
+    pub fn pick(&mut self, selection: ChoiceSelection) -> Grade {
+        self.answer(Answer::Pick(selection))
+    }
+
+    pub fn answer(&mut self, answer: Answer) -> Grade {
+        let grade = self.challenge.grade(&answer);
+        self.tick();
+        if !self.in_progress {
+            // too late, the time ran out before the answer came
+            return grade;
+        }
+        self.answered += 1;
+        self.points += grade.points;
+        self.max_points += grade.max_points;
+        self.scheduler
+            .record_answer(&self.challenge.unit_pairs(), grade.is_correct());
+        if grade.is_correct() {
+            self.correct += 1;
+            self.correction = None;
+        } else {
+            self.correction = Some(self.challenge.solution());
+            if let GameMode::Practice { retry_after } = self.mode {
+                self.retries
+                    .push_back((self.answered + retry_after, self.challenge.clone()));
+            }
+        }
+        // every graded answer counts, mistakes included, only rounds keep their ratings fixed
+        if !matches!(self.mode, GameMode::Round { .. }) {
+            let score = grade.points as Float / grade.max_points as Float;
+            for unit_pair in self.challenge.unit_pairs() {
+                self.rating_per_unit_pair
+                    .entry(unit_pair)
+                    .or_default()
+                    .update(self.challenge.difficulty, score);
+            }
+        }
+        let game_over = match self.mode {
+            GameMode::SuddenDeath => !grade.is_correct(),
+            GameMode::Blitz { .. } => false,
+            GameMode::Lives { .. } => self.lives_left() == Some(0),
+            GameMode::Round { cards, .. } => self.answered >= cards,
+            GameMode::Practice { .. } => false,
+        };
+        if game_over {
+            self.end();
+        } else if matches!(self.retries.front(), Some((due, _)) if *due <= self.answered) {
+            let (_, missed) = self.retries.pop_front().unwrap();
+            self.challenge = Self::similar_challenge(
+                &missed,
+                &self.rating_per_unit_pair,
+                self.mode,
+                self.curve.as_ref(),
+                &self.settings,
+            );
+        } else {
+            let unit_pair = self
+                .scheduler
+                .choose(&self.rating_per_unit_pair, &mut rand::thread_rng());
+            self.challenge = Self::next_challenge(
+                unit_pair,
+                &self.rating_per_unit_pair,
+                self.mode,
+                self.curve.as_ref(),
+                &self.settings,
+            );
+        }
+        grade
+    }
+
+    /// Fresh challenge of the same kind, context and units as `missed`, generated for the current rating.
+    fn similar_challenge(
+        missed: &Challenge,
+        rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>,
+        mode: GameMode,
+        curve: &dyn DifficultyCurve,
+        settings: &GenerationSettings,
+    ) -> Challenge {
+        let units: Vec<Unit> = missed.choices.iter().map(|choice| choice.unit).collect();
+        match missed.kind {
+            ChallengeKind::Pick => Challenge::generate(
+                &units,
+                missed.context,
+                Self::difficulty_of(rating_per_unit_pair, mode, &units, settings),
+                curve,
+                settings,
+            ),
+            ChallengeKind::Order => Challenge::generate_order(
+                &units,
+                missed.context,
+                Self::difficulty_of(rating_per_unit_pair, mode, &units, settings),
+                curve,
+                settings,
+            ),
+            ChallengeKind::Estimate { target, .. } => Challenge::generate_estimate(
+                (units[0], target),
+                missed.context,
+                Self::difficulty_of(rating_per_unit_pair, mode, &[units[0], target], settings),
+                settings,
+            ),
+        }
+    }
+
+    /// Current rating of every unit pair.
+    pub fn ratings(&self) -> Vec<((Unit, Unit), Rating)> {
+        let mut ratings: Vec<_> = self
+            .rating_per_unit_pair
+            .iter()
+            .map(|(unit_pair, rating)| (*unit_pair, *rating))
+            .collect();
+        ratings.sort_by_key(|(unit_pair, _)| *unit_pair);
+        ratings
+    }
+
+    /// Solution of the last challenge if it was answered wrong, e.g. "30 C is hotter".
+    pub fn correction(&self) -> Option<&str> {
+        self.correction.as_deref()
+    }
+
+    /// Cards remaining in rounds, `None` for the other games.
+    pub fn cards_left(&self) -> Option<usize> {
+        match self.mode {
+            GameMode::Round { cards, .. } => Some(cards.saturating_sub(self.answered)),
+            _ => None,
+        }
+    }
+
+    /// Lives remaining for games with lives, `None` for the others.
+    pub fn lives_left(&self) -> Option<usize> {
+        match self.mode {
+            GameMode::Lives { lives, .. } => Some(lives.saturating_sub(self.answered - self.correct)),
+            _ => None,
+        }
+    }
+
+    /// Ends the game if its time is up. Timed games should call this regularly to stop once the time runs out.
+    pub fn tick(&mut self) {
+        if self.in_progress && self.time_left() == Some(Duration::ZERO) {
+            self.end();
+        }
+    }
+
+    /// Time remaining for timed games, `None` for the others.
+    pub fn time_left(&self) -> Option<Duration> {
+        match self.mode {
+            GameMode::Blitz { time_limit } => Some(time_limit.saturating_sub(self.elapsed())),
+            _ => None,
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.ended_at
+            .unwrap_or_else(|| self.clock.now())
+            .saturating_sub(self.started_at)
+    }
+
+    fn end(&mut self) {
+        self.in_progress = false;
+        self.ended_at = Some(self.clock.now());
+    }
+
+    pub fn summary(&self) -> GameSummary {
+        let duration = match self.mode {
+            GameMode::Blitz { time_limit } => self.elapsed().min(time_limit),
+            _ => self.elapsed(),
+        };
+        GameSummary {
+            mode: self.mode,
+            answered: self.answered,
+            correct: self.correct,
+            points: self.points,
+            max_points: self.max_points,
+            duration,
+            ratings: self.ratings(),
+            correction: self.correction.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    /// Clock that only moves when told to, clones share the time.
+    #[derive(Clone, Debug, Default)]
+    struct FakeClock(Rc<Cell<Duration>>);
+
+    impl FakeClock {
+        fn advance(&self, duration: Duration) {
+            self.0.set(self.0.get() + duration);
+        }
+    }
+
+    impl Clock for FakeClock {
+        fn now(&self) -> Duration {
+            self.0.get()
+        }
+    }
+
+    fn temperature_game(mode: GameMode, clock: &FakeClock) -> Game {
+        let mut game = Game::new_with_single_quantity(
+            Quantity::Temperature,
+            &[Category::Conversion],
+            mode,
+            Box::new(clock.clone()),
+        );
+        game.challenge = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 30.0), (Unit::Fahrenheit, 0.0)]);
+        game
+    }
+
+    fn rating_of(game: &Game, unit_pair: (Unit, Unit)) -> Rating {
+        *game.rating_per_unit_pair.get(&unit_pair).unwrap()
+    }
+
+    fn floats_close_enough(a: Float, b: Float) -> bool {
+        (a - b).abs() < 0.001
+    }
+
+    /// Generates challenges for `units` in every one of `contexts` at a few levels, checks that they're all valid and
+    /// returns them for checks specific to the units.
+    fn assert_generates_valid(units: &[Unit], contexts: &[Context], settings: &GenerationSettings) -> Vec<Challenge> {
+        let curve = GeometricCurve::default();
+        let mut challenges = Vec::new();
+        for context in contexts {
+            for level in &[0, 10, 100] {
+                for _ in 0..50 {
+                    let challenge =
+                        Challenge::generate(units, *context, rating::level_difficulty(*level), &curve, settings);
+                    assert_eq!(challenge.validate(settings), Ok(()), "{:?}", challenge);
+                    assert_eq!(challenge.context, *context);
+                    challenges.push(challenge);
+                }
+            }
+        }
+        challenges
+    }
+
+    #[test]
+    fn conversions_work() {
+        assert_eq!(
+            convert(-40.0, Unit::Celsius, Unit::Fahrenheit),
+            convert(-40.0, Unit::Fahrenheit, Unit::Celsius)
+        );
+        assert!(floats_close_enough(
+            convert(0.0, Unit::Celsius, Unit::Celsius).unwrap(),
+            0.0
+        ));
+        assert!(floats_close_enough(
+            convert(0.0, Unit::Celsius, Unit::Fahrenheit).unwrap(),
+            32.0
+        ));
+        assert!(floats_close_enough(
+            convert(100.0, Unit::Celsius, Unit::Fahrenheit).unwrap(),
+            212.0
+        ));
+        assert!(floats_close_enough(
+            convert(0.0, Unit::Fahrenheit, Unit::Celsius).unwrap(),
+            -17.777777
+        ));
+        assert!(floats_close_enough(
+            convert(100.0, Unit::Fahrenheit, Unit::Celsius).unwrap(),
+            37.777777
+        ));
+
+        assert_eq!(
+            convert(0.0, Unit::Kilometer, Unit::NauticalMile),
+            convert(0.0, Unit::NauticalMile, Unit::Kilometer)
+        );
+        assert!(floats_close_enough(
+            convert(1000.0, Unit::NauticalMile, Unit::Kilometer).unwrap(),
+            1852.0
+        ));
+
+        assert!(floats_close_enough(
+            convert(8700.0, Unit::Joule, Unit::Calorie).unwrap(),
+            2079.35
+        ));
+    }
+
+    #[test]
+    fn temperature_differences_convert_without_offset() {
+        assert!(floats_close_enough(
+            convert(10.0, Unit::CelsiusDifference, Unit::FahrenheitDifference).unwrap(),
+            18.0
+        ));
+        assert!(floats_close_enough(
+            convert(10.0, Unit::Celsius, Unit::Fahrenheit).unwrap(),
+            50.0
+        ));
+        assert_eq!(convert(10.0, Unit::Celsius, Unit::FahrenheitDifference), None);
+
+        let challenge = Challenge::new(
+            Polarity::Greater,
+            &[(Unit::FahrenheitDifference, 15.0), (Unit::CelsiusDifference, 9.0)],
+        );
+        assert_eq!(challenge.prompt(), "Which is a bigger change?");
+        assert_eq!(challenge.solution(), "9 ΔC is a bigger change");
+    }
+
+    #[test]
+    fn table_scales_compare_by_steps() {
+        let settings = GenerationSettings::default();
+        assert!(floats_close_enough(
+            convert(5.0, Unit::Beaufort, Unit::Knot).unwrap(),
+            19.0
+        ));
+        let within = Challenge::new(Polarity::Greater, &[(Unit::Knot, 20.0), (Unit::Beaufort, 5.0)]);
+        assert_eq!(within.solution(), "20 kn = 5 Bft");
+        let above = Challenge::new(
+            Polarity::Greater,
+            &[(Unit::KilometerPerHour, 45.0), (Unit::Beaufort, 5.0)],
+        );
+        assert_eq!(above.solution(), "45 km/h is stronger");
+
+        // neighbouring steps are at least 1/12 apart so the default min gap always holds
+        for (a, b) in Quantity::WindSpeed.unit_pairs() {
+            assert_generates_valid(&[a, b], &Context::all_for(&[a, b]), &settings);
+        }
+    }
+
+    #[test]
+    fn substances_convert_by_their_context() {
+        let settings = GenerationSettings::default();
+        let cup_or_grams = || Challenge::new(Polarity::Greater, &[(Unit::Cup, 1.0), (Unit::Gram, 150.0)]);
+        assert_eq!(cup_or_grams().in_context(Context::Flour).solution(), "150 g is heavier");
+        assert_eq!(cup_or_grams().in_context(Context::Water).solution(), "1 cup is heavier");
+        assert_eq!(convert(1.0, Unit::Cup, Unit::Gram), None);
+
+        for (a, b) in Quantity::Volume
+            .unit_pairs_in(Category::Substance)
+            .into_iter()
+            .chain(Quantity::Concentration.unit_pairs())
+        {
+            assert_generates_valid(&[a, b], &Context::all_for(&[a, b]), &settings);
+        }
+    }
+
+    #[test]
+    fn extreme_lengths_are_written_in_powers_of_ten() {
+        let settings = GenerationSettings::default();
+        let challenge = Challenge::new(Polarity::Greater, &[(Unit::Kilometer, 1.5e8), (Unit::Au, 2.0)])
+            .in_context(Context::SolarSystem);
+        assert_eq!(challenge.choices[0].to_string(), "1.5×10⁸ km");
+        assert_eq!(challenge.solution(), "2 AU is longer");
+
+        // only four significant digits are shown, that's far below any gap that decides an answer
+        let shown_closely = |value: Float| {
+            let shown: Float = format!("{:.3e}", value).parse().unwrap();
+            (shown - value).abs() <= value.abs() * settings.min_relative_gap / 10.0
+        };
+        for (a, b) in Quantity::ExtremeLength.unit_pairs() {
+            for challenge in assert_generates_valid(&[a, b], &Context::all_for(&[a, b]), &settings) {
+                assert!(
+                    challenge.choices.iter().all(|choice| shown_closely(choice.value)),
+                    "{:?}",
+                    challenge
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn decibels_are_logarithmic() {
+        assert!(floats_close_enough(
+            convert(100.0, Unit::Milliwatt, Unit::DecibelMilliwatt).unwrap(),
+            20.0
+        ));
+        assert!(floats_close_enough(
+            convert(23.0, Unit::DecibelMilliwatt, Unit::Milliwatt).unwrap(),
+            199.52623
+        ));
+        let settings = GenerationSettings::default();
+        let challenge = Challenge::new(
+            Polarity::Greater,
+            &[(Unit::DecibelMilliwatt, 23.0), (Unit::Milliwatt, 150.0)],
+        );
+        assert_eq!(challenge.solution(), "23 dBm is stronger");
+
+        // 1 dB off is 26% more power, too much at 10% tolerance, while 0.3 dB (7%) is fine
+        let estimate = Challenge {
+            kind: ChallengeKind::Estimate {
+                target: Unit::DecibelMilliwatt,
+                tolerance: 0.1,
+            },
+            ..Challenge::new(Polarity::Greater, &[(Unit::Milliwatt, 100.0)])
+        };
+        assert_eq!(estimate.grade(&Answer::Estimate(21.0)).points, 0);
+        assert_eq!(estimate.grade(&Answer::Estimate(20.3)).points, 1);
+
+        let units = [Unit::DecibelMilliwatt, Unit::Milliwatt];
+        for challenge in assert_generates_valid(&units, &Context::all_for(&units), &settings) {
+            assert!(
+                challenge.choices.iter().all(|choice| choice.value > -100.0),
+                "{:?}",
+                challenge
+            );
+        }
+    }
+
+    #[test]
+    fn units_ordered_properly() {
+        assert!(Unit::Fahrenheit < Unit::Celsius);
+        assert!(Unit::Foot < Unit::Meter);
+        assert_eq!(
+            [Unit::Mile, Unit::Kilometer, Unit::NauticalMile].iter().min(),
+            Some(&Unit::Kilometer)
+        );
+        assert!(Unit::FluidOunce > Unit::Millilitre);
+    }
+
+    #[test]
+    fn units_order_follows_delta() {
+        for a in ALL_UNITS {
+            for b in ALL_UNITS {
+                if let Some(delta) = delta_convert(1.0, *a, *b) {
+                    if delta < 1.0 {
+                        assert!(*a < *b, "delta = {:?} so {:?} < {:?}", delta, *a, *b);
+                    } else if delta > 1.0 {
+                        assert!(*a > *b, "delta = {:?} so {:?} > {:?}", delta, *a, *b);
+                    } else {
+                        assert_eq!(*a, *b, "delta = {:?} so {:?} == {:?}", delta, *a, *b);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generated_challenges_keep_min_gap() {
+        let settings = GenerationSettings {
+            min_relative_gap: 0.1,
+            equal_probability: 0.0,
+            less_probability: 0.5,
+            ..GenerationSettings::default()
+        };
+        // fractional deltas make the min gap do all the work at high levels, whole ones rely on rounding
+        let whole = GeometricCurve {
+            min_delta: 1.0,
+            ..GeometricCurve::default()
+        };
+        for curve in &[GeometricCurve::default(), whole] {
+            for quantity in &[
+                Quantity::Temperature,
+                Quantity::TemperatureDifference,
+                Quantity::Length,
+                Quantity::Volume,
+                Quantity::Mass,
+                Quantity::ExtremeLength,
+            ] {
+                for unit_pair in ALL_CATEGORIES
+                    .iter()
+                    .flat_map(|category| quantity.unit_pairs_in(*category))
+                {
+                    let units = [unit_pair.0, unit_pair.1];
+                    for context in Context::all_for(&units) {
+                        for level in &[0, 10, 100] {
+                            for _ in 0..100 {
+                                let difficulty = rating::level_difficulty(*level);
+                                let challenge = Challenge::generate(&units, context, difficulty, curve, &settings);
+                                assert_eq!(challenge.validate(&settings), Ok(()), "{:?}", challenge);
+                                assert_eq!(challenge.context, context);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn high_levels_use_decimals_up_to_unit_precision() {
+        let settings = GenerationSettings::default();
+        let curve = GeometricCurve::default();
+        let has_decimals = |challenge: &Challenge| challenge.choices.iter().any(|choice| choice.value.fract() != 0.0);
+        let within_precision = |challenge: &Challenge| {
+            challenge.choices.iter().all(|choice| {
+                let scaled = choice.value * (10.0 as Float).powi(choice.unit.precision() as i32);
+                (scaled - scaled.round()).abs() < 0.01
+            })
+        };
+        let units = [Unit::Celsius, Unit::Fahrenheit];
+        let low: Vec<_> = (0..100)
+            .map(|_| Challenge::generate(&units, Context::Weather, rating::level_difficulty(0), &curve, &settings))
+            .collect();
+        assert!(!low.iter().any(has_decimals));
+        let high: Vec<_> = (0..100)
+            .map(|_| {
+                Challenge::generate(
+                    &units,
+                    Context::Weather,
+                    rating::level_difficulty(80),
+                    &curve,
+                    &settings,
+                )
+            })
+            .collect();
+        assert!(high.iter().any(has_decimals));
+        assert!(high.iter().all(within_precision));
+    }
+
+    #[test]
+    fn values_are_formatted_with_unit_precision() {
+        let choice = |unit, value| Challenge::new(Polarity::Greater, &[(unit, value)]).choices[0].to_string();
+        assert_eq!(choice(Unit::Celsius, 21.5), "21.5 C");
+        assert_eq!(choice(Unit::Celsius, 30.0), "30 C");
+        assert_eq!(choice(Unit::Celsius, -0.01), "0 C");
+        assert_eq!(choice(Unit::Kilogram, 1.25), "1.25 kg");
+        assert_eq!(choice(Unit::Joule, 12.7), "13 J");
+    }
+
+    #[test]
+    fn unit_pairs_are_ordered() {
+        for unit in ALL_UNITS {
+            for category in ALL_CATEGORIES {
+                for (a, b) in unit.quantity().unit_pairs_in(*category) {
+                    assert_eq!((a, b), a.pair_with(b));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn within_system_pairs_are_rated_separately() {
+        let clock = FakeClock::default();
+        let conversions = Game::new_with_single_quantity(
+            Quantity::Volume,
+            &[Category::Conversion],
+            GameMode::practice(),
+            Box::new(clock.clone()),
+        );
+        let unit_pair = (Unit::Millilitre, Unit::Litre);
+        assert!(conversions.ratings().iter().all(|(pair, _)| *pair != unit_pair));
+
+        let mut game =
+            Game::new_with_single_quantity(Quantity::Volume, ALL_CATEGORIES, GameMode::practice(), Box::new(clock));
+        assert_eq!(rating_of(&game, unit_pair), Rating::default());
+        game.challenge = Challenge::new(Polarity::Greater, &[(Unit::Millilitre, 1500.0), (Unit::Litre, 1.2)])
+            .in_context(Context::Drink);
+        assert_eq!(game.challenge.choices[1].to_string(), "1.2 L");
+        game.pick(ChoiceSelection::Nth(0));
+        assert!(rating_of(&game, unit_pair).value > Rating::default().value);
+        assert_eq!(
+            rating_of(&game, (Unit::Millilitre, Unit::FluidOunce)),
+            Rating::default()
+        );
+    }
+
+    #[test]
+    fn compound_values_are_whole_subdivisions() {
+        let settings = GenerationSettings {
+            expression_probability: 0.0,
+            ..GenerationSettings::default()
+        };
+        for challenge in assert_generates_valid(&[Unit::Centimeter, Unit::Foot], &[Context::BodyHeight], &settings) {
+            let feet = challenge
+                .choices
+                .iter()
+                .find(|choice| choice.unit == Unit::Foot)
+                .unwrap();
+            assert_eq!(feet.notation, Notation::Compound(Compound::FeetInches));
+            let inches = feet.value * 12.0;
+            assert!((inches - inches.round()).abs() < 0.01, "{:?}", challenge);
+            assert!(feet.to_string().contains(" ft"), "{}", feet);
+        }
+        let movie =
+            Challenge::new(Polarity::Greater, &[(Unit::Hour, 1.5), (Unit::Minute, 95.0)]).in_context(Context::Movie);
+        assert_eq!(movie.choices[0].to_string(), "1 h 30 min");
+        assert_eq!(movie.solution(), "95 min is longer");
+    }
+
+    #[test]
+    fn fractional_values_are_exact() {
+        let settings = GenerationSettings {
+            expression_probability: 0.0,
+            ..GenerationSettings::default()
+        };
+        for (units, context, fraction_unit, denominator) in &[
+            ([Unit::Centimeter, Unit::Inch], Context::Woodwork, Unit::Inch, 16.0),
+            ([Unit::Millilitre, Unit::Cup], Context::Recipe, Unit::Cup, 4.0),
+        ] {
+            for challenge in assert_generates_valid(units, &[*context], &settings) {
+                let choice = challenge
+                    .choices
+                    .iter()
+                    .find(|choice| choice.unit == *fraction_unit)
+                    .unwrap();
+                let parts = choice.value * denominator;
+                assert!((parts - parts.round()).abs() < 0.01, "{:?}", challenge);
+                assert!(!choice.to_string().contains('.'), "{}", choice);
+            }
+        }
+        let woodwork = Challenge::new(Polarity::Greater, &[(Unit::Centimeter, 14.0), (Unit::Inch, 5.375)])
+            .in_context(Context::Woodwork);
+        assert_eq!(woodwork.choices[1].to_string(), "5 3/8 in");
+    }
+
+    #[test]
+    fn expressions_are_left_for_advanced_players() {
+        let settings = GenerationSettings {
+            expression_probability: 1.0,
+            ..GenerationSettings::default()
+        };
+        let curve = GeometricCurve::default();
+        let units = [Unit::Foot, Unit::Meter];
+        for _ in 0..100 {
+            let challenge = Challenge::generate(&units, Context::Room, rating::level_difficulty(0), &curve, &settings);
+            assert!(challenge.choices.iter().all(|choice| choice.expression.is_none()));
+        }
+        let mut expressions = 0;
+        for _ in 0..100 {
+            let challenge = Challenge::generate(&units, Context::Room, rating::level_difficulty(20), &curve, &settings);
+            assert_eq!(challenge.validate(&settings), Ok(()), "{:?}", challenge);
+            for choice in challenge.choices.iter() {
+                if let Some(expression) = choice.expression {
+                    assert!(floats_close_enough(
+                        expression.evaluate(choice.unit).unwrap(),
+                        choice.value
+                    ));
+                    expressions += 1;
+                }
+            }
+        }
+        assert!(expressions > 50, "{}", expressions);
+
+        let mut challenge = Challenge::new(Polarity::Greater, &[(Unit::Foot, 14.0 / 3.0), (Unit::Meter, 1.4)]);
+        challenge.choices[0].expression = Some(Expression::Sum((Unit::Foot, 3.0), (Unit::Inch, 20.0)));
+        assert_eq!(challenge.solution(), "3 ft + 20 in is longer");
+    }
+
+    #[test]
+    fn multiple_choice_challenges_have_single_answer() {
+        let settings = GenerationSettings {
+            less_probability: 0.5,
+            ..GenerationSettings::default()
+        };
+        let curve = GeometricCurve::default();
+        let units = [Unit::Kilometer, Unit::Mile, Unit::NauticalMile];
+        for level in &[0, 10, 100] {
+            for _ in 0..100 {
+                let challenge = Challenge::generate(
+                    &units,
+                    Context::Flight,
+                    rating::level_difficulty(*level),
+                    &curve,
+                    &settings,
+                );
+                assert_eq!(challenge.choices.len(), 3);
+                assert_eq!(challenge.validate(&settings), Ok(()), "{:?}", challenge);
+                let correct = (0..3)
+                    .filter(|idx| challenge.is_correct(ChoiceSelection::Nth(*idx)))
+                    .count();
+                assert_eq!(correct, 1, "{:?}", challenge);
+            }
+        }
+    }
+
+    #[test]
+    fn multiple_choice_picks_the_extreme() {
+        let mut challenge = Challenge::new(
+            Polarity::Greater,
+            &[(Unit::Kilometer, 5.0), (Unit::Mile, 3.0), (Unit::NauticalMile, 2.8)],
+        );
+        assert!(challenge.is_correct(ChoiceSelection::Nth(2)));
+        assert!(!challenge.is_correct(ChoiceSelection::Nth(0)));
+        assert!(!challenge.is_correct(ChoiceSelection::Nth(1)));
+        assert_eq!(challenge.prompt(), "Which is the longest?");
+
+        challenge.polarity = Polarity::Less;
+        assert!(challenge.is_correct(ChoiceSelection::Nth(1)));
+        assert_eq!(challenge.prompt(), "Which is the shortest?");
+    }
+
+    #[test]
+    fn validation_rejects_degenerate_challenges() {
+        let settings = GenerationSettings::default();
+        let almost_tie = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 30.0), (Unit::Fahrenheit, 87.0)]);
+        assert!(matches!(
+            almost_tie.validate(&settings),
+            Err(InvalidChallenge::GapTooSmall { .. })
+        ));
+        let same_unit = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 10.0), (Unit::Celsius, 20.0)]);
+        assert_eq!(same_unit.validate(&settings), Err(InvalidChallenge::SameUnit));
+        let single = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 10.0)]);
+        assert_eq!(single.validate(&settings), Err(InvalidChallenge::WrongChoiceCount));
+    }
+
+    #[test]
+    fn temperature_gaps_go_by_the_range() {
+        let settings = GenerationSettings::default();
+        // half a degree is as close to zero as it is to forty, 2 C apart is clear anywhere in the weather's 75 C
+        let near_zero = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 0.5), (Unit::Fahrenheit, 32.0)]);
+        let near_forty = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 40.5), (Unit::Fahrenheit, 104.0)]);
+        for challenge in &[near_zero, near_forty] {
+            assert!(matches!(
+                challenge.validate(&settings),
+                Err(InvalidChallenge::GapTooSmall { .. })
+            ));
+        }
+        let apart = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 2.0), (Unit::Fahrenheit, 32.0)]);
+        assert_eq!(apart.validate(&settings), Ok(()));
+    }
+
+    #[test]
+    fn equal_sides_need_equal_selection() {
+        let settings = GenerationSettings::default();
+        let tie = Challenge::new(Polarity::Less, &[(Unit::Celsius, 10.0), (Unit::Fahrenheit, 50.0)]);
+        assert_eq!(tie.validate(&settings), Ok(()));
+        assert!(tie.is_correct(ChoiceSelection::Equal));
+        assert!(!tie.is_correct(ChoiceSelection::Nth(0)));
+        assert!(!tie.is_correct(ChoiceSelection::Nth(1)));
+
+        // a few tenths of a degree apart which both units show
+        let close = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 47.0), (Unit::Fahrenheit, 117.0)]);
+        assert!(!close.is_correct(ChoiceSelection::Equal));
+        assert!(matches!(
+            close.validate(&settings),
+            Err(InvalidChallenge::GapTooSmall { .. })
+        ));
+    }
+
+    #[test]
+    fn less_polarity_reverses_the_answer() {
+        let mut challenge = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 30.0), (Unit::Fahrenheit, 50.0)]);
+        assert!(challenge.is_correct(ChoiceSelection::Nth(0)));
+        assert!(!challenge.is_correct(ChoiceSelection::Nth(1)));
+        assert_eq!(challenge.prompt(), "Which is hotter?");
+
+        challenge.polarity = Polarity::Less;
+        assert!(!challenge.is_correct(ChoiceSelection::Nth(0)));
+        assert!(challenge.is_correct(ChoiceSelection::Nth(1)));
+        assert_eq!(challenge.prompt(), "Which is colder?");
+    }
+
+    #[test]
+    fn ordering_challenges_are_strictly_ordered() {
+        let settings = GenerationSettings::default();
+        let units = [
+            Unit::Kilometer,
+            Unit::Mile,
+            Unit::NauticalMile,
+            Unit::Mile,
+            Unit::Kilometer,
+        ];
+        let curve = GeometricCurve::default();
+        for level in &[0, 10, 100] {
+            for _ in 0..100 {
+                let challenge = Challenge::generate_order(
+                    &units,
+                    Context::Flight,
+                    rating::level_difficulty(*level),
+                    &curve,
+                    &settings,
+                );
+                assert_eq!(challenge.kind, ChallengeKind::Order);
+                assert_eq!(challenge.validate(&settings), Ok(()), "{:?}", challenge);
+                let grade = challenge.grade(&Answer::Order(challenge.ranking()));
+                assert!(grade.is_correct(), "{:?}", challenge);
+            }
+        }
+    }
+
+    #[test]
+    fn orderings_that_dont_fit_fall_back_to_picking() {
+        // three gaps of half the range can't fit in it
+        let settings = GenerationSettings {
+            min_relative_gap: 0.5,
+            ..GenerationSettings::default()
+        };
+        let units = [Unit::Celsius, Unit::Fahrenheit, Unit::Celsius, Unit::Fahrenheit];
+        let challenge = Challenge::generate_order(
+            &units,
+            Context::Weather,
+            rating::BASE_DIFFICULTY,
+            &GeometricCurve::default(),
+            &settings,
+        );
+        assert_eq!(challenge.kind, ChallengeKind::Pick);
+        assert_eq!(challenge.validate(&settings), Ok(()), "{:?}", challenge);
+    }
+
+    #[test]
+    fn ordering_gives_partial_credit() {
+        let challenge = Challenge {
+            kind: ChallengeKind::Order,
+            ..Challenge::new(
+                Polarity::Less,
+                &[(Unit::Kilometer, 5.0), (Unit::Mile, 2.0), (Unit::NauticalMile, 5.0)],
+            )
+        };
+        assert_eq!(challenge.prompt(), "Order from shortest to longest");
+        let grade = |order: Vec<usize>| challenge.grade(&Answer::Order(order));
+        assert_eq!(
+            grade(vec![1, 0, 2]),
+            Grade {
+                points: 2,
+                max_points: 2
+            }
+        );
+        assert_eq!(
+            grade(vec![0, 1, 2]),
+            Grade {
+                points: 1,
+                max_points: 2
+            }
+        );
+        assert_eq!(
+            grade(vec![2, 0, 1]),
+            Grade {
+                points: 0,
+                max_points: 2
+            }
+        );
+        assert_eq!(
+            grade(vec![0, 0, 1]),
+            Grade {
+                points: 0,
+                max_points: 2
+            }
+        );
+        assert_eq!(
+            challenge.grade(&Answer::Pick(ChoiceSelection::Nth(0))),
+            Grade {
+                points: 0,
+                max_points: 1
+            }
+        );
+    }
+
+    #[test]
+    fn estimation_is_graded_by_relative_error() {
+        let settings = GenerationSettings::default();
+        let challenge = Challenge {
+            kind: ChallengeKind::Estimate {
+                target: Unit::Mile,
+                tolerance: 0.1,
+            },
+            ..Challenge::new(Polarity::Greater, &[(Unit::Kilometer, 16.0)])
+        };
+        assert_eq!(challenge.validate(&settings), Ok(()));
+        assert_eq!(challenge.prompt(), "How much is that in mi?");
+        let grade = |value| challenge.grade(&Answer::Estimate(value)).is_correct();
+        assert!(grade(10.0));
+        assert!(grade(9.2));
+        assert!(!grade(8.5));
+        assert!(!grade(11.5));
+    }
+
+    #[test]
+    fn table_scale_estimates_are_graded_by_step() {
+        // 20 kn is about 5.2 Bft on the interpolated scale but the card shows force 5
+        let challenge = Challenge {
+            kind: ChallengeKind::Estimate {
+                target: Unit::Beaufort,
+                tolerance: 0.01,
+            },
+            ..Challenge::new(Polarity::Greater, &[(Unit::Knot, 20.0)])
+        };
+        assert_eq!(challenge.solution(), "20 kn = 5 Bft");
+        let grade = |value| challenge.grade(&Answer::Estimate(value)).is_correct();
+        assert!(grade(5.0));
+        assert!(!grade(4.0));
+        assert!(!grade(6.0));
+    }
+
+    #[test]
+    fn temperature_estimates_are_graded_against_the_range() {
+        // 5% of the weather's 75 C is the same few degrees near zero and far from it
+        let estimate = |fahrenheit: Float| Challenge {
+            kind: ChallengeKind::Estimate {
+                target: Unit::Celsius,
+                tolerance: 0.05,
+            },
+            ..Challenge::new(Polarity::Greater, &[(Unit::Fahrenheit, fahrenheit)])
+        };
+        let grade = |fahrenheit: Float, value: Float| estimate(fahrenheit).grade(&Answer::Estimate(value)).is_correct();
+        assert!(grade(35.0, 1.0));
+        assert!(grade(35.0, -1.0));
+        assert!(!grade(35.0, 6.0));
+        assert!(grade(104.0, 38.0));
+        assert!(!grade(104.0, 31.0));
+    }
+
+    #[test]
+    fn estimation_tolerance_tightens_with_level() {
+        let settings = GenerationSettings::default();
+        let unit_pair = Unit::Kilometer.pair_with(Unit::Mile);
+        let tolerance = |level| {
+            let difficulty = rating::level_difficulty(level);
+            match Challenge::generate_estimate(unit_pair, Context::Commute, difficulty, &settings).kind {
+                ChallengeKind::Estimate { tolerance, .. } => tolerance,
+                kind => panic!("unexpected kind: {:?}", kind),
+            }
+        };
+        assert!(floats_close_enough(tolerance(0), settings.estimation_tolerance));
+        assert!(tolerance(5) < tolerance(0));
+        assert!(floats_close_enough(tolerance(100), 0.01));
+    }
+
+    #[test]
+    fn ties_are_generated_on_request() {
+        let settings = GenerationSettings {
+            equal_probability: 1.0,
+            ..GenerationSettings::default()
+        };
+        let challenge = Challenge::generate(
+            &[Unit::Celsius, Unit::Fahrenheit],
+            Context::Weather,
+            rating::BASE_DIFFICULTY,
+            &GeometricCurve::default(),
+            &settings,
+        );
+        assert!(challenge.is_correct(ChoiceSelection::Equal), "{:?}", challenge);
+    }
+
+    #[test]
+    fn challenges_stay_within_their_context() {
+        let clock = FakeClock::default();
+        let settings = GenerationSettings::default();
+        for unit in ALL_UNITS {
+            let mut game = Game::new_with_single_quantity(
+                unit.quantity(),
+                ALL_CATEGORIES,
+                GameMode::practice(),
+                Box::new(clock.clone()),
+            );
+            for _ in 0..200 {
+                let challenge = game.challenge.clone();
+                assert_eq!(challenge.validate(&settings), Ok(()), "{:?}", challenge);
+                for choice in &challenge.choices {
+                    assert!(challenge.context.units().contains(&choice.unit), "{:?}", challenge);
+                }
+                let answer = match challenge.kind {
+                    ChallengeKind::Pick => Answer::Pick(ChoiceSelection::Nth(0)),
+                    ChallengeKind::Order => Answer::Order(challenge.ranking()),
+                    ChallengeKind::Estimate { .. } => Answer::Estimate(0.0),
+                };
+                game.answer(answer);
+            }
+        }
+    }
+
+    #[test]
+    fn new_game_is_in_progress() {
+        let game = Game::new_with_single_quantity(
+            Quantity::Temperature,
+            &[Category::Conversion],
+            GameMode::SuddenDeath,
+            Box::new(FakeClock::default()),
+        );
+        assert_eq!(game.in_progress, true);
+    }
+
+    #[test]
+    fn correct_pick_raises_rating() {
+        let unit_pair = Unit::Celsius.pair_with(Unit::Fahrenheit);
+        let mut game = temperature_game(GameMode::SuddenDeath, &FakeClock::default());
+        game.pick(ChoiceSelection::Nth(0));
+        assert!(rating_of(&game, unit_pair).value > Rating::default().value);
+        assert!(rating_of(&game, unit_pair).deviation < Rating::default().deviation);
+        assert_eq!(game.in_progress, true);
+    }
+
+    #[test]
+    fn wrong_pick_stops_game() {
+        let unit_pair = Unit::Celsius.pair_with(Unit::Fahrenheit);
+        let mut game = temperature_game(GameMode::SuddenDeath, &FakeClock::default());
+        game.pick(ChoiceSelection::Nth(1));
+        assert!(rating_of(&game, unit_pair).value < Rating::default().value);
+        assert_eq!(game.in_progress, false);
+    }
+
+    #[test]
+    fn partly_right_order_ends_sudden_death() {
+        let mut game = temperature_game(GameMode::SuddenDeath, &FakeClock::default());
+        game.challenge = Challenge {
+            kind: ChallengeKind::Order,
+            ..Challenge::new(
+                Polarity::Greater,
+                &[(Unit::Celsius, 30.0), (Unit::Fahrenheit, 0.0), (Unit::Celsius, 0.0)],
+            )
+        };
+        let grade = game.answer(Answer::Order(vec![0, 1, 2]));
+        assert_eq!(
+            grade,
+            Grade {
+                points: 1,
+                max_points: 2
+            }
+        );
+        assert_eq!(game.in_progress, false);
+        assert!(floats_close_enough(game.summary().accuracy(), 0.5));
+    }
+
+    #[test]
+    fn mistakes_cost_lives() {
+        let unit_pair = Unit::Celsius.pair_with(Unit::Fahrenheit);
+        let mut game = temperature_game(GameMode::lives(), &FakeClock::default());
+        for lives_left in (0..DEFAULT_LIVES).rev() {
+            game.challenge = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 30.0), (Unit::Fahrenheit, 0.0)]);
+            game.pick(ChoiceSelection::Nth(1));
+            assert_eq!(game.lives_left(), Some(lives_left));
+            assert_eq!(game.in_progress, lives_left > 0);
+            assert_eq!(game.correction(), Some("30 C is hotter"));
+        }
+        assert!(rating_of(&game, unit_pair).value < Rating::default().value);
+    }
+
+    #[test]
+    fn mistakes_lower_the_rating() {
+        let unit_pair = Unit::Celsius.pair_with(Unit::Fahrenheit);
+        for mode in &[GameMode::blitz(), GameMode::lives(), GameMode::practice()] {
+            let mut game = temperature_game(*mode, &FakeClock::default());
+            game.pick(ChoiceSelection::Nth(1));
+            assert!(
+                rating_of(&game, unit_pair).value < Rating::default().value,
+                "{:?}",
+                mode
+            );
+            assert_eq!(game.in_progress, true);
+        }
+    }
+
+    #[test]
+    fn solution_spells_out_the_answer() {
+        let tie = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 10.0), (Unit::Fahrenheit, 50.0)]);
+        assert_eq!(tie.solution(), "10 C = 50 F");
+        let order = Challenge {
+            kind: ChallengeKind::Order,
+            ..Challenge::new(
+                Polarity::Less,
+                &[(Unit::NauticalMile, 1.0), (Unit::Kilometer, 1.0), (Unit::Mile, 1.0)],
+            )
+        };
+        assert_eq!(order.solution(), "1 km < 1 mi < 1 NM");
+    }
+
+    #[test]
+    fn round_ends_with_a_grade() {
+        let unit_pair = Unit::Celsius.pair_with(Unit::Fahrenheit);
+        let mode = GameMode::Round { cards: 4, level: 3 };
+        let mut game = temperature_game(mode, &FakeClock::default());
+        let generated = Game::new_with_single_quantity(
+            Quantity::Temperature,
+            &[Category::Conversion],
+            mode,
+            Box::new(FakeClock::default()),
+        );
+        assert!(floats_close_enough(
+            generated.challenge.difficulty,
+            rating::level_difficulty(3)
+        ));
+        for selection in &[
+            ChoiceSelection::Nth(0),
+            ChoiceSelection::Nth(1),
+            ChoiceSelection::Nth(0),
+            ChoiceSelection::Nth(0),
+        ] {
+            assert_eq!(game.in_progress, true);
+            game.challenge = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 30.0), (Unit::Fahrenheit, 0.0)]);
+            game.pick(*selection);
+        }
+        assert_eq!(game.in_progress, false);
+        assert_eq!(game.cards_left(), Some(0));
+        // ratings stay put so that rounds are comparable
+        assert_eq!(rating_of(&game, unit_pair), Rating::default());
+        let summary = game.summary();
+        assert!(floats_close_enough(summary.accuracy(), 0.75));
+        assert_eq!(summary.letter_grade(), Some(LetterGrade::C));
+    }
+
+    #[test]
+    fn letter_grades_follow_accuracy() {
+        assert_eq!(LetterGrade::from_accuracy(1.0), LetterGrade::A);
+        assert_eq!(LetterGrade::from_accuracy(0.9), LetterGrade::A);
+        assert_eq!(LetterGrade::from_accuracy(0.85), LetterGrade::B);
+        assert_eq!(LetterGrade::from_accuracy(0.6), LetterGrade::D);
+        assert_eq!(LetterGrade::from_accuracy(0.0), LetterGrade::F);
+    }
+
+    #[test]
+    fn practice_lowers_the_rating_and_retries() {
+        let unit_pair = Unit::Kilometer.pair_with(Unit::Mile);
+        let missed = Challenge::new(Polarity::Greater, &[(Unit::Kilometer, 1.0), (Unit::Mile, 1.0)]);
+        let mut game = Game::new_with_single_quantity(
+            Quantity::Length,
+            &[Category::Conversion],
+            GameMode::practice(),
+            Box::new(FakeClock::default()),
+        );
+        game.challenge = missed.clone();
+        game.pick(ChoiceSelection::Nth(0));
+        assert_eq!(game.in_progress, true);
+        assert!(rating_of(&game, unit_pair).value < Rating::default().value);
+
+        for _ in 0..DEFAULT_PRACTICE_RETRY_AFTER {
+            game.challenge = Challenge::new(Polarity::Greater, &[(Unit::Kilometer, 2.0), (Unit::NauticalMile, 1.0)]);
+            game.pick(ChoiceSelection::Nth(0));
+        }
+        assert_eq!(game.challenge.unit_pairs(), vec![unit_pair]);
+        assert_eq!(game.retries.len(), 0);
+    }
+
+    #[test]
+    fn blitz_continues_after_mistakes() {
+        let clock = FakeClock::default();
+        let mut game = temperature_game(GameMode::blitz(), &clock);
+        clock.advance(Duration::from_secs(10));
+        game.pick(ChoiceSelection::Nth(1));
+        assert_eq!(game.in_progress, true);
+        assert_eq!(game.time_left(), Some(Duration::from_secs(50)));
+    }
+
+    #[test]
+    fn blitz_ends_when_time_is_up() {
+        let clock = FakeClock::default();
+        let mut game = temperature_game(GameMode::blitz(), &clock);
+        game.pick(ChoiceSelection::Nth(0));
+        game.challenge = Challenge::new(Polarity::Greater, &[(Unit::Celsius, 30.0), (Unit::Fahrenheit, 0.0)]);
+        clock.advance(Duration::from_secs(59));
+        game.tick();
+        assert_eq!(game.in_progress, true);
+        game.pick(ChoiceSelection::Nth(1));
+
+        // the last answer comes too late and doesn't count
+        clock.advance(Duration::from_secs(2));
+        game.pick(ChoiceSelection::Nth(0));
+        assert_eq!(game.in_progress, false);
+        assert_eq!(game.time_left(), Some(Duration::ZERO));
+        assert_eq!(
+            game.summary(),
+            GameSummary {
+                mode: GameMode::blitz(),
+                answered: 2,
+                correct: 1,
+                points: 1,
+                max_points: 2,
+                duration: BLITZ_TIME_LIMIT,
+                ratings: game.ratings(),
+                correction: Some("30 C is hotter".to_string()),
+            }
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use super::{Float, Unit};
+
+/// How a value is written for the player.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Notation {
+    /// Decimal places up to the unit's precision, e.g. "21.5 C".
+    Decimal,
+    /// The unit and its subdivision, e.g. "5 ft 11 in".
+    Compound(Compound),
+    /// Whole part and a reduced fraction of `denominator`ths, e.g. "5 3/8 in" for sixteenths.
+    Fraction { denominator: u32 },
+    /// Mantissa and a power of ten, e.g. "1.5×10⁸ km". Engineering notation keeps the exponent a multiple of three,
+    /// e.g. "450×10⁻³ µm".
+    Scientific { engineering: bool },
+}
+
+impl Notation {
+    /// Smallest difference that can be written while keeping differences of `resolution`, none for decimals since
+    /// their precision depends on the unit. Scientific values are rounded to the power of ten below `resolution`.
+    pub fn step(&self, resolution: Float) -> Option<Float> {
+        match self {
+            Notation::Decimal => None,
+            Notation::Compound(compound) => Some(compound.step()),
+            Notation::Fraction { denominator } => Some(1.0 / *denominator as Float),
+            Notation::Scientific { .. } => Some((10.0 as Float).powi(resolution.log10().floor() as i32)),
+        }
+    }
+
+    /// Writes `value` of `unit` including the unit.
+    pub fn format(&self, unit: Unit, value: Float) -> String {
+        match self {
+            Notation::Decimal => format!("{} {}", unit.format_value(value), unit),
+            Notation::Compound(compound) => compound.format(value),
+            Notation::Fraction { denominator } => format!("{} {}", format_fraction(value, *denominator), unit),
+            Notation::Scientific { engineering } => format!("{} {}", format_scientific(value, *engineering), unit),
+        }
+    }
+}
+
+fn gcd(a: i64, b: i64) -> i64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// Writes `value` rounded to the nearest `denominator`th, e.g. "3/4" or "5 3/8".
+fn format_fraction(value: Float, denominator: u32) -> String {
+    let denominator = denominator as i64;
+    let numerator = (value * denominator as Float).round() as i64;
+    let (whole, rest) = (numerator / denominator, (numerator % denominator).abs());
+    let divisor = gcd(rest, denominator);
+    match (whole, rest) {
+        (_, 0) => whole.to_string(),
+        (0, _) if numerator < 0 => format!("-{}/{}", rest / divisor, denominator / divisor),
+        (0, _) => format!("{}/{}", rest / divisor, denominator / divisor),
+        _ => format!("{} {}/{}", whole, rest / divisor, denominator / divisor),
+    }
+}
+
+/// Writes `value` with up to four significant digits and a superscript exponent (left out when it's zero), e.g.
+/// "1.5×10⁸" or "45×10⁻⁹" for engineering notation.
+fn format_scientific(value: Float, engineering: bool) -> String {
+    // formatting first makes the mantissa carry over when it rounds up to 10
+    let formatted = format!("{:.3e}", value);
+    let (mantissa, exponent) = formatted.split_once('e').expect("no exponent");
+    let (mut mantissa, mut exponent): (Float, i32) = (mantissa.parse().unwrap(), exponent.parse().unwrap());
+    if engineering {
+        let shift = exponent.rem_euclid(3);
+        mantissa *= (10.0 as Float).powi(shift);
+        exponent -= shift;
+    }
+    let mantissa = format!("{:.3}", mantissa);
+    let mantissa = mantissa.trim_end_matches('0').trim_end_matches('.');
+    if exponent == 0 {
+        return mantissa.to_string();
+    }
+    let superscript: String = exponent
+        .to_string()
+        .chars()
+        .map(|digit| match digit {
+            '-' => '⁻',
+            _ => "⁰¹²³⁴⁵⁶⁷⁸⁹".chars().nth(digit.to_digit(10).unwrap() as usize).unwrap(),
+        })
+        .collect();
+    format!("{}×10{}", mantissa, superscript)
+}
+
+/// Value written in a unit and its subdivision, e.g. "5 ft 11 in".
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Compound {
+    FeetInches,
+    PoundsOunces,
+    StonesPounds,
+    HoursMinutes,
+}
+
+impl Compound {
+    /// Symbols of the unit and of its subdivision together with how many subdivisions make the unit.
+    fn parts(&self) -> (&'static str, &'static str, Float) {
+        match self {
+            Compound::FeetInches => ("ft", "in", 12.0),
+            Compound::PoundsOunces => ("lb", "oz", 16.0),
+            Compound::StonesPounds => ("st", "lb", 14.0),
+            Compound::HoursMinutes => ("h", "min", 60.0),
+        }
+    }
+
+    /// Smallest difference that can be written, a single subdivision.
+    pub fn step(&self) -> Float {
+        1.0 / self.parts().2
+    }
+
+    /// Writes `value` (in the unit) rounded to whole subdivisions, parts that are zero are left out.
+    pub fn format(&self, value: Float) -> String {
+        let (unit, subunit, per_unit) = self.parts();
+        let subdivisions = (value * per_unit).round() as i64;
+        let (whole, rest) = (subdivisions / per_unit as i64, subdivisions % per_unit as i64);
+        match (whole, rest) {
+            (_, 0) => format!("{} {}", whole, unit),
+            (0, _) => format!("{} {}", rest, subunit),
+            _ => format!("{} {} {} {}", whole, unit, rest, subunit),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compound_values_are_split() {
+        assert_eq!(Compound::FeetInches.format(5.0 + 11.0 / 12.0), "5 ft 11 in");
+        assert_eq!(Compound::FeetInches.format(5.99), "6 ft");
+        assert_eq!(Compound::PoundsOunces.format(2.0 + 3.0 / 16.0), "2 lb 3 oz");
+        assert_eq!(Compound::PoundsOunces.format(0.25), "4 oz");
+        assert_eq!(Compound::StonesPounds.format(11.5), "11 st 7 lb");
+        assert_eq!(Compound::HoursMinutes.format(1.75), "1 h 45 min");
+    }
+
+    #[test]
+    fn fractions_are_reduced() {
+        let sixteenths = Notation::Fraction { denominator: 16 };
+        assert_eq!(sixteenths.format(Unit::Inch, 5.375), "5 3/8 in");
+        assert_eq!(sixteenths.format(Unit::Inch, 0.75), "3/4 in");
+        assert_eq!(sixteenths.format(Unit::Inch, 2.999), "3 in");
+        assert_eq!(
+            Notation::Fraction { denominator: 4 }.format(Unit::Cup, 1.3),
+            "1 1/4 cup"
+        );
+        assert_eq!(format_fraction(-0.5, 4), "-1/2");
+    }
+
+    #[test]
+    fn powers_of_ten_are_superscripts() {
+        let scientific = Notation::Scientific { engineering: false };
+        assert_eq!(scientific.format(Unit::Kilometer, 1.5e8), "1.5×10⁸ km");
+        assert_eq!(scientific.format(Unit::Kilometer, 9.9999e8), "1×10⁹ km");
+        assert_eq!(scientific.format(Unit::Kilometer, 4.5), "4.5 km");
+        assert_eq!(scientific.format(Unit::Kilometer, 0.00025), "2.5×10⁻⁴ km");
+
+        let engineering = Notation::Scientific { engineering: true };
+        assert_eq!(engineering.format(Unit::Kilometer, 0.45), "450×10⁻³ km");
+        assert_eq!(engineering.format(Unit::Kilometer, 12340.0), "12.34×10³ km");
+        assert_eq!(engineering.format(Unit::Kilometer, 450.0), "450 km");
+
+        assert!((scientific.step(0.003).unwrap() - 0.001).abs() < 1e-9);
+        assert_eq!(scientific.step(2.5e7), Some(1e7));
+    }
+}
@@ -0,0 +1,98 @@
+use super::{Float, Level};
+
+/// Difficulty of the easiest challenges (level 0), harder ones go up by `LEVEL_STEP` for every level.
+pub const BASE_DIFFICULTY: Float = 1000.0;
+pub const LEVEL_STEP: Float = 100.0;
+/// New players start a bit above the base difficulty so that their first challenges are the easiest ones.
+pub const INITIAL_RATING: Float = 1200.0;
+pub const INITIAL_DEVIATION: Float = 350.0;
+const MIN_DEVIATION: Float = 50.0;
+/// How much of the deviation is left after every update.
+const DEVIATION_DECAY: Float = 0.9;
+/// Biggest rating change per challenge, scaled down as the deviation shrinks.
+const MAX_K: Float = 64.0;
+/// Usual Elo scale, 400 points of difference means 10:1 odds.
+const SCALE: Float = 400.0;
+
+/// Difficulty of challenges at `level`.
+pub fn level_difficulty(level: Level) -> Float {
+    BASE_DIFFICULTY + LEVEL_STEP * level as Float
+}
+
+/// Level (possibly fractional) matching `difficulty`, never below 0.
+pub fn difficulty_level(difficulty: Float) -> Float {
+    ((difficulty - BASE_DIFFICULTY) / LEVEL_STEP).max(0.0)
+}
+
+/// Skill of the player on a unit pair in Elo points together with its uncertainty, similar to Glicko's rating
+/// deviation. The deviation starts high so that the first few answers move the rating a lot.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Rating {
+    pub value: Float,
+    pub deviation: Float,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Self {
+            value: INITIAL_RATING,
+            deviation: INITIAL_DEVIATION,
+        }
+    }
+}
+
+impl Rating {
+    /// Chance of the player answering a challenge of `difficulty` correctly.
+    pub fn success_probability(&self, difficulty: Float) -> Float {
+        1.0 / (1.0 + (10.0 as Float).powf((difficulty - self.value) / SCALE))
+    }
+
+    /// Difficulty at which the player succeeds with `probability`, never easier than level 0.
+    pub fn difficulty_for(&self, probability: Float) -> Float {
+        (self.value + SCALE * (1.0 / probability - 1.0).log10()).max(BASE_DIFFICULTY)
+    }
+
+    /// Moves the rating after scoring `score` (between 0 and 1) on a challenge of `difficulty`.
+    pub fn update(&mut self, difficulty: Float, score: Float) {
+        let k = MAX_K * self.deviation / INITIAL_DEVIATION;
+        self.value += k * (score - self.success_probability(difficulty));
+        self.deviation = (self.deviation * DEVIATION_DECAY).max(MIN_DEVIATION);
+    }
+
+    /// Level of challenges the player beats half of the time.
+    pub fn level(&self) -> Float {
+        difficulty_level(self.value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn target_difficulty_gives_target_probability() {
+        let rating = Rating {
+            value: 1500.0,
+            deviation: INITIAL_DEVIATION,
+        };
+        let difficulty = rating.difficulty_for(0.75);
+        assert!(difficulty < rating.value);
+        assert!((rating.success_probability(difficulty) - 0.75).abs() < 0.001);
+        assert_eq!(Rating::default().difficulty_for(0.99), BASE_DIFFICULTY);
+    }
+
+    #[test]
+    fn surprises_move_the_rating_more() {
+        let mut expected_win = Rating::default();
+        expected_win.update(BASE_DIFFICULTY, 1.0);
+        let mut unexpected_win = Rating::default();
+        unexpected_win.update(level_difficulty(10), 1.0);
+        assert!(expected_win.value > INITIAL_RATING);
+        assert!(unexpected_win.value > expected_win.value);
+        assert!(expected_win.deviation < INITIAL_DEVIATION);
+
+        let mut loss = Rating::default();
+        loss.update(BASE_DIFFICULTY, 0.0);
+        assert!(loss.value < INITIAL_RATING);
+    }
+}
@@ -0,0 +1,72 @@
+use super::{Float, Unit};
+
+/// Scale given by a table instead of a formula, e.g. Beaufort force by wind speed. Every step covers a range of the
+/// base unit, conversions interpolate between the middles of the steps while comparisons go by the steps alone.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Scale {
+    pub base: Unit,
+    /// Upper bounds (exclusive) of all steps but the last one, in the base unit. The first step starts at 0 and the
+    /// last one is as wide as the one before it.
+    upper_bounds: &'static [Float],
+}
+
+/// Wind force by speed in knots, 20 kn is force 5 for example.
+pub const BEAUFORT: Scale = Scale {
+    base: Unit::Knot,
+    upper_bounds: &[0.5, 3.5, 6.5, 10.5, 16.5, 21.5, 27.5, 33.5, 40.5, 47.5, 55.5, 63.5],
+};
+
+impl Scale {
+    /// Step `base` falls into.
+    pub fn step_of(&self, base: Float) -> Float {
+        self.upper_bounds.iter().filter(|bound| base >= **bound).count() as Float
+    }
+
+    /// Base value at a (fractional) position on the scale, whole positions are the middles of their steps. Positions
+    /// outside of the table are extrapolated.
+    pub fn base_value_at(&self, position: Float) -> Float {
+        let idx = position.floor().clamp(0.0, (self.upper_bounds.len() - 1) as Float) as usize;
+        let (low, high) = (self.middle(idx), self.middle(idx + 1));
+        low + (position - idx as Float) * (high - low)
+    }
+
+    /// Inverse of `base_value_at`.
+    pub fn position_of(&self, base: Float) -> Float {
+        let idx = (0..self.upper_bounds.len())
+            .rev()
+            .find(|idx| self.middle(*idx) <= base)
+            .unwrap_or(0);
+        let (low, high) = (self.middle(idx), self.middle(idx + 1));
+        idx as Float + (base - low) / (high - low)
+    }
+
+    fn middle(&self, step: usize) -> Float {
+        let bounds = self.upper_bounds;
+        let low = if step == 0 { 0.0 } else { bounds[step - 1] };
+        let high = match bounds.get(step) {
+            Some(high) => *high,
+            None => low + (low - bounds[bounds.len() - 2]),
+        };
+        (low + high) / 2.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beaufort_steps_are_looked_up() {
+        assert_eq!(BEAUFORT.step_of(0.2), 0.0);
+        assert_eq!(BEAUFORT.step_of(20.0), 5.0);
+        assert_eq!(BEAUFORT.step_of(21.5), 6.0);
+        assert_eq!(BEAUFORT.step_of(80.0), 12.0);
+
+        assert_eq!(BEAUFORT.base_value_at(5.0), 19.0);
+        assert_eq!(BEAUFORT.base_value_at(12.0), 67.5);
+        for position in &[0.0, 0.7, 5.3, 11.9, 12.5] {
+            let base = BEAUFORT.base_value_at(*position);
+            assert!((BEAUFORT.position_of(base) - position).abs() < 0.001, "{}", position);
+        }
+    }
+}
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use rand::seq::SliceRandom;
+use rand::RngCore;
+
+use super::{Float, Rating, Unit};
+
+/// Strategy for choosing the unit pair of the next challenge.
+pub trait PairScheduler: fmt::Debug {
+    /// Picks one of the pairs in `rating_per_unit_pair`.
+    fn choose(&mut self, rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>, rng: &mut dyn RngCore) -> (Unit, Unit);
+
+    /// Lets the scheduler know how the player did on an answer involving `unit_pairs`.
+    fn record_answer(&mut self, _unit_pairs: &[(Unit, Unit)], _correct: bool) {}
+}
+
+/// Pairs sorted so that the choice depends only on the RNG and not on the hash map's iteration order.
+fn sorted_pairs(rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>) -> Vec<((Unit, Unit), Rating)> {
+    let mut pairs: Vec<_> = rating_per_unit_pair
+        .iter()
+        .map(|(unit_pair, rating)| (*unit_pair, *rating))
+        .collect();
+    pairs.sort_by_key(|(unit_pair, _)| *unit_pair);
+    pairs
+}
+
+/// Every pair has the same chance.
+#[derive(Clone, Debug, Default)]
+pub struct UniformScheduler;
+
+impl PairScheduler for UniformScheduler {
+    fn choose(&mut self, rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>, rng: &mut dyn RngCore) -> (Unit, Unit) {
+        sorted_pairs(rating_per_unit_pair).choose(rng).expect("no unit pairs").0
+    }
+}
+
+/// Prefers pairs with a low rating or recent mistakes. Pairs not seen for a while slowly gain weight so that even
+/// mastered ones come back from time to time, like in spaced repetition.
+#[derive(Clone, Debug)]
+pub struct WeightedScheduler {
+    /// Weight added for every recent mistake on a pair.
+    pub mistake_weight: Float,
+    /// How much of the recent mistakes is left after every answer.
+    pub mistake_decay: Float,
+    /// Relative weight gained for every card a pair wasn't chosen.
+    pub staleness_weight: Float,
+    recent_mistakes: HashMap<(Unit, Unit), Float>,
+    last_chosen: HashMap<(Unit, Unit), usize>,
+    chosen_count: usize,
+}
+
+impl Default for WeightedScheduler {
+    fn default() -> Self {
+        Self {
+            mistake_weight: 1.0,
+            mistake_decay: 0.8,
+            staleness_weight: 0.1,
+            recent_mistakes: HashMap::new(),
+            last_chosen: HashMap::new(),
+            chosen_count: 0,
+        }
+    }
+}
+
+impl WeightedScheduler {
+    fn weight(&self, unit_pair: (Unit, Unit), rating: &Rating) -> Float {
+        let weakness = 1.0 / (1.0 + rating.level())
+            + self.mistake_weight * self.recent_mistakes.get(&unit_pair).copied().unwrap_or(0.0);
+        let since_chosen = self.chosen_count - self.last_chosen.get(&unit_pair).copied().unwrap_or(0);
+        weakness * (1.0 + self.staleness_weight * since_chosen as Float)
+    }
+}
+
+impl PairScheduler for WeightedScheduler {
+    fn choose(&mut self, rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>, rng: &mut dyn RngCore) -> (Unit, Unit) {
+        let unit_pair = sorted_pairs(rating_per_unit_pair)
+            .choose_weighted(rng, |(unit_pair, rating)| self.weight(*unit_pair, rating))
+            .expect("no unit pairs")
+            .0;
+        self.chosen_count += 1;
+        self.last_chosen.insert(unit_pair, self.chosen_count);
+        unit_pair
+    }
+
+    fn record_answer(&mut self, unit_pairs: &[(Unit, Unit)], correct: bool) {
+        for mistakes in self.recent_mistakes.values_mut() {
+            *mistakes *= self.mistake_decay;
+        }
+        if !correct {
+            for unit_pair in unit_pairs {
+                *self.recent_mistakes.entry(*unit_pair).or_insert(0.0) += 1.0;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::rating::level_difficulty;
+    use super::super::Level;
+    use super::*;
+
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    const WEAK: (Unit, Unit) = (Unit::Foot, Unit::Meter);
+    const STRONG: (Unit, Unit) = (Unit::Kilometer, Unit::Mile);
+
+    fn levels(weak: Level, strong: Level) -> HashMap<(Unit, Unit), Rating> {
+        let at_level = |level| Rating {
+            value: level_difficulty(level),
+            ..Rating::default()
+        };
+        let mut rating_per_unit_pair = HashMap::new();
+        rating_per_unit_pair.insert(WEAK, at_level(weak));
+        rating_per_unit_pair.insert(STRONG, at_level(strong));
+        rating_per_unit_pair
+    }
+
+    fn count_weak(scheduler: &mut dyn PairScheduler, rating_per_unit_pair: &HashMap<(Unit, Unit), Rating>) -> usize {
+        let mut rng = StdRng::seed_from_u64(36);
+        (0..1000)
+            .filter(|_| scheduler.choose(rating_per_unit_pair, &mut rng) == WEAK)
+            .count()
+    }
+
+    #[test]
+    fn schedulers_are_deterministic_with_seeded_rng() {
+        let rating_per_unit_pair = levels(0, 5);
+        let draw = |scheduler: &mut dyn PairScheduler| {
+            let mut rng = StdRng::seed_from_u64(7);
+            (0..20)
+                .map(|_| scheduler.choose(&rating_per_unit_pair, &mut rng))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(draw(&mut UniformScheduler), draw(&mut UniformScheduler));
+        assert_eq!(
+            draw(&mut WeightedScheduler::default()),
+            draw(&mut WeightedScheduler::default())
+        );
+    }
+
+    #[test]
+    fn uniform_scheduler_ignores_levels() {
+        let weak = count_weak(&mut UniformScheduler, &levels(0, 10));
+        assert!((400..600).contains(&weak), "{}", weak);
+    }
+
+    #[test]
+    fn weighted_scheduler_prefers_weak_pairs_but_revisits_strong_ones() {
+        let weak = count_weak(&mut WeightedScheduler::default(), &levels(0, 10));
+        assert!(weak > 600, "{}", weak);
+        assert!(weak < 1000, "{}", weak);
+    }
+
+    #[test]
+    fn weighted_scheduler_prefers_recent_mistakes() {
+        let mut scheduler = WeightedScheduler::default();
+        scheduler.record_answer(&[WEAK], false);
+        scheduler.record_answer(&[STRONG], true);
+        let weak = count_weak(&mut scheduler, &levels(3, 3));
+        assert!(weak > 550, "{}", weak);
+    }
+
+    #[test]
+    fn mistakes_decay_once_per_answer() {
+        let mut scheduler = WeightedScheduler::default();
+        scheduler.record_answer(&[WEAK], false);
+        // an ordering involves several pairs but it's still a single answer
+        scheduler.record_answer(
+            &[
+                STRONG,
+                (Unit::Kilometer, Unit::NauticalMile),
+                (Unit::Mile, Unit::NauticalMile),
+            ],
+            true,
+        );
+        assert_eq!(scheduler.recent_mistakes[&WEAK], scheduler.mistake_decay);
+    }
+}
@@ -0,0 +1,84 @@
+use super::{convert, Float, Quantity, Unit};
+
+/// Material a measurement is taken of, for conversions that depend on it, e.g. a cup of flour weighs less than a cup
+/// of sugar.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Substance {
+    Flour,
+    Sugar,
+    Water,
+    Glucose,
+    Cholesterol,
+}
+
+impl Substance {
+    /// Grams per millilitre, none for substances that aren't measured by volume.
+    fn density(&self) -> Option<Float> {
+        match self {
+            Substance::Flour => Some(0.53),
+            Substance::Sugar => Some(0.85),
+            Substance::Water => Some(1.0),
+            Substance::Glucose | Substance::Cholesterol => None,
+        }
+    }
+
+    /// Grams per mole, none for mixtures.
+    fn molar_mass(&self) -> Option<Float> {
+        match self {
+            Substance::Glucose => Some(180.16),
+            Substance::Cholesterol => Some(386.65),
+            Substance::Flour | Substance::Sugar | Substance::Water => None,
+        }
+    }
+
+    /// Converts between units that only convert for a given substance (volume and mass or mass and molar
+    /// concentration), none for the rest.
+    pub fn convert(&self, value: Float, from: Unit, to: Unit) -> Option<Float> {
+        match (from, to) {
+            // a decilitre is a tenth of a litre and a millimole of the substance weighs its molar mass in milligrams
+            (Unit::MilligramPerDecilitre, Unit::MillimolePerLitre) => Some(value * 10.0 / self.molar_mass()?),
+            (Unit::MillimolePerLitre, Unit::MilligramPerDecilitre) => Some(value * self.molar_mass()? / 10.0),
+            _ => match (from.quantity(), to.quantity()) {
+                (Quantity::Volume, Quantity::Mass) => convert(
+                    convert(value, from, Unit::Millilitre)? * self.density()?,
+                    Unit::Gram,
+                    to,
+                ),
+                (Quantity::Mass, Quantity::Volume) => convert(
+                    convert(value, from, Unit::Gram)? / self.density()?,
+                    Unit::Millilitre,
+                    to,
+                ),
+                _ => None,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn conversions_depend_on_the_substance() {
+        let cup_of = |substance: Substance| substance.convert(1.0, Unit::Cup, Unit::Gram).unwrap();
+        assert!((cup_of(Substance::Flour) - 125.4).abs() < 0.1);
+        assert!((cup_of(Substance::Sugar) - 201.1).abs() < 0.1);
+        assert!(cup_of(Substance::Flour) < cup_of(Substance::Water));
+        assert!((Substance::Water.convert(1.0, Unit::Kilogram, Unit::Litre).unwrap() - 1.0).abs() < 0.001);
+        assert_eq!(Substance::Glucose.convert(1.0, Unit::Cup, Unit::Gram), None);
+
+        let glucose = Substance::Glucose
+            .convert(100.0, Unit::MilligramPerDecilitre, Unit::MillimolePerLitre)
+            .unwrap();
+        assert!((glucose - 5.55).abs() < 0.01);
+        let cholesterol = Substance::Cholesterol
+            .convert(5.0, Unit::MillimolePerLitre, Unit::MilligramPerDecilitre)
+            .unwrap();
+        assert!((cholesterol - 193.3).abs() < 0.1);
+        assert_eq!(
+            Substance::Flour.convert(100.0, Unit::MilligramPerDecilitre, Unit::MillimolePerLitre),
+            None
+        );
+    }
+}
@@ -1,17 +1,55 @@
+use std::time::{Duration, Instant};
+
 mod logic;
 
 const KEY_TO_GAME: &[(&str, logic::Quantity)] = &[
     ("t", logic::Quantity::Temperature),
+    ("r", logic::Quantity::TemperatureDifference),
     ("l", logic::Quantity::Length),
     ("a", logic::Quantity::Area),
     ("v", logic::Quantity::Volume),
     ("m", logic::Quantity::Mass),
     ("e", logic::Quantity::Energy),
     ("p", logic::Quantity::Pressure),
+    ("d", logic::Quantity::Time),
+    ("b", logic::Quantity::WindSpeed),
+    ("s", logic::Quantity::Power),
+    ("n", logic::Quantity::Concentration),
+    ("x", logic::Quantity::ExtremeLength),
 ];
 
+#[derive(Debug)]
+struct InstantClock(Instant);
+
+impl logic::Clock for InstantClock {
+    fn now(&self) -> Duration {
+        self.0.elapsed()
+    }
+}
+
+/// Whether `category` is played, shown next to its toggle.
+fn on_off(categories: &[logic::Category], category: logic::Category) -> &'static str {
+    if categories.contains(&category) {
+        "on"
+    } else {
+        "off"
+    }
+}
+
+/// Adds `category` to the played ones or removes it, conversions are always played.
+fn toggle(categories: &mut Vec<logic::Category>, category: logic::Category) {
+    match categories.iter().position(|played| *played == category) {
+        Some(idx) => {
+            categories.remove(idx);
+        }
+        None => categories.push(category),
+    }
+}
+
 fn main() {
     let mut quit = false;
+    let mut mode = logic::GameMode::SuddenDeath;
+    let mut categories = vec![logic::Category::Conversion];
 
     while !quit {
         let mut choice: Option<Vec<logic::Quantity>> = None;
@@ -20,6 +58,15 @@ fn main() {
                 println!("{} = {:?}", key, quantity);
             }
             println!("* = all");
+            println!("g = switch game mode ({:?})", mode);
+            println!(
+                "w = toggle units within one system ({})",
+                on_off(&categories, logic::Category::WithinSystem)
+            );
+            println!(
+                "c = toggle units for a substance ({})",
+                on_off(&categories, logic::Category::Substance)
+            );
             println!("q = quit");
             let mut buf = String::new();
             std::io::stdin().read_line(&mut buf).unwrap();
@@ -32,6 +79,17 @@ fn main() {
             }
             match buf.as_str() {
                 "*" => choice = Some(KEY_TO_GAME.iter().map(|(_, quantity)| *quantity).collect()),
+                "g" => {
+                    mode = match mode {
+                        logic::GameMode::SuddenDeath => logic::GameMode::blitz(),
+                        logic::GameMode::Blitz { .. } => logic::GameMode::lives(),
+                        logic::GameMode::Lives { .. } => logic::GameMode::round(),
+                        logic::GameMode::Round { .. } => logic::GameMode::practice(),
+                        logic::GameMode::Practice { .. } => logic::GameMode::SuddenDeath,
+                    }
+                }
+                "w" => toggle(&mut categories, logic::Category::WithinSystem),
+                "c" => toggle(&mut categories, logic::Category::Substance),
                 "q" => quit = true,
                 _ => {}
             }
@@ -42,19 +100,105 @@ fn main() {
         }
 
         // TODO: change to multiple quantities once implemented in logic
-        let mut game = logic::Game::new_with_single_quantity(choice.unwrap().first().unwrap().clone());
+        let mut game = logic::Game::new_with_single_quantity(
+            choice.unwrap().first().unwrap().clone(),
+            &categories,
+            mode,
+            Box::new(InstantClock(Instant::now())),
+        );
         while !quit && game.in_progress {
+            if let Some(correction) = game.correction() {
+                println!("wrong: {}", correction);
+            }
             println!("{:?}", game);
-            println!("1? 2? q?");
+            if let Some(time_left) = game.time_left() {
+                println!("{}s left", time_left.as_secs());
+            }
+            if let Some(lives_left) = game.lives_left() {
+                println!("{} lives left", lives_left);
+            }
+            if let Some(cards_left) = game.cards_left() {
+                println!("{} cards left", cards_left);
+            }
+            println!("{} ({})", game.challenge.prompt(), game.challenge.context);
+            for (idx, choice) in game.challenge.choices.iter().enumerate() {
+                println!("{}: {}", idx + 1, choice);
+            }
+            let choices = game.challenge.choices.len();
             let mut buf = String::new();
-            std::io::stdin().read_line(&mut buf).unwrap();
-            match buf.trim() {
-                "1" => game.pick(logic::ChoiceSelection::Left),
-                "2" => game.pick(logic::ChoiceSelection::Right),
-                "q" => quit = true,
-                _ => println!("Invalid selection: {:?}", buf),
+            match game.challenge.kind {
+                logic::ChallengeKind::Pick => {
+                    let options: Vec<String> = (1..=choices).map(|idx| idx.to_string()).collect();
+                    println!("{}? =? q?", options.join("/"));
+                    std::io::stdin().read_line(&mut buf).unwrap();
+                    let selection = match buf.trim() {
+                        "=" => Some(logic::ChoiceSelection::Equal),
+                        "q" => {
+                            quit = true;
+                            None
+                        }
+                        selection => match selection.parse::<usize>() {
+                            Ok(idx) if (1..=choices).contains(&idx) => Some(logic::ChoiceSelection::Nth(idx - 1)),
+                            _ => {
+                                println!("Invalid selection: {:?}", buf);
+                                None
+                            }
+                        },
+                    };
+                    if let Some(selection) = selection {
+                        game.pick(selection);
+                    }
+                }
+                logic::ChallengeKind::Order => {
+                    let example: Vec<String> = (1..=choices).rev().map(|idx| idx.to_string()).collect();
+                    println!("order (e.g. {})? q?", example.join(" "));
+                    std::io::stdin().read_line(&mut buf).unwrap();
+                    let order: Result<Vec<usize>, _> = buf.split_whitespace().map(|idx| idx.parse::<usize>()).collect();
+                    match (buf.trim(), order) {
+                        ("q", _) => quit = true,
+                        (_, Ok(order))
+                            if order.len() == choices && order.iter().all(|idx| (1..=choices).contains(idx)) =>
+                        {
+                            let grade = game.answer(logic::Answer::Order(order.iter().map(|idx| idx - 1).collect()));
+                            println!("{}/{} pairs in order", grade.points, grade.max_points);
+                        }
+                        _ => println!("Invalid order: {:?}", buf),
+                    }
+                }
+                logic::ChallengeKind::Estimate { target, .. } => {
+                    println!("value? q?");
+                    std::io::stdin().read_line(&mut buf).unwrap();
+                    match (buf.trim(), buf.trim().parse::<f32>()) {
+                        ("q", _) => quit = true,
+                        (_, Ok(value)) => {
+                            let expected = game.challenge.expected_estimate().unwrap_or(f32::NAN);
+                            let expected = game.challenge.context.notation(target).format(target, expected);
+                            let grade = game.answer(logic::Answer::Estimate(value));
+                            println!(
+                                "{} (expected {})",
+                                if grade.is_correct() { "correct" } else { "wrong" },
+                                expected
+                            );
+                        }
+                        _ => println!("Invalid value: {:?}", buf),
+                    }
+                }
             }
         }
-        println!("{:?}", game);
+        let summary = game.summary();
+        if let Some(ref correction) = summary.correction {
+            println!("wrong: {}", correction);
+        }
+        println!("{:?}", summary);
+        for (unit_pair, rating) in &summary.ratings {
+            println!(
+                "{} / {}: {:.0} +- {:.0}",
+                unit_pair.0, unit_pair.1, rating.value, rating.deviation
+            );
+        }
+        println!("{:.0}% accuracy", summary.accuracy() * 100.0);
+        if let Some(letter_grade) = summary.letter_grade() {
+            println!("grade: {}", letter_grade);
+        }
     }
 }